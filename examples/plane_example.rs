@@ -19,7 +19,7 @@ fn main() -> std::io::Result<()> {
     );
 
     let mut world = World::new();
-    world.light_source = Some(light);
+    world.add_light(light);
 
     let mut side_color = Material::default();
     side_color.color = Tuple::color(1.0, 0.9, 0.9);
@@ -20,7 +20,7 @@ fn main() -> std::io::Result<()> {
     );
 
     let mut world = World::new();
-    world.light_source = Some(light);
+    world.add_light(light);
 
     let mut side_color = Material::default();
     let mut side_pattern = Pattern::checkers(
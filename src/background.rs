@@ -0,0 +1,240 @@
+use canvas::Canvas;
+#[cfg(test)]
+use float_eq;
+use std::f32::consts::PI;
+use tuples::Tuple;
+
+/// Six square images forming the inside of a cube around the scene, with
+/// `front` facing +z.
+pub struct CubeMap {
+    pub left: Canvas,
+    pub front: Canvas,
+    pub right: Canvas,
+    pub back: Canvas,
+    pub up: Canvas,
+    pub down: Canvas,
+}
+
+impl CubeMap {
+    pub fn color_at(&self, direction: Tuple) -> Tuple {
+        let coord = direction
+            .x
+            .abs()
+            .max(direction.y.abs())
+            .max(direction.z.abs());
+        // Scale the direction onto the surface of a cube from -1 to 1
+        let p = direction / coord;
+        let (face, u, v) = if coord == direction.x {
+            (&self.right, 1.0 - p.z, p.y + 1.0)
+        } else if coord == -direction.x {
+            (&self.left, p.z + 1.0, p.y + 1.0)
+        } else if coord == direction.y {
+            (&self.up, p.x + 1.0, 1.0 - p.z)
+        } else if coord == -direction.y {
+            (&self.down, p.x + 1.0, p.z + 1.0)
+        } else if coord == direction.z {
+            (&self.front, p.x + 1.0, p.y + 1.0)
+        } else {
+            (&self.back, 1.0 - p.x, p.y + 1.0)
+        };
+        image_at(face, u / 2.0, v / 2.0)
+    }
+}
+
+/// What a ray sees when it doesn't hit anything in the world.
+pub enum Background {
+    Color(Tuple),
+    /// Blends from `bottom`, looking straight down, to `top`, looking
+    /// straight up.
+    Gradient {
+        bottom: Tuple,
+        top: Tuple,
+    },
+    /// An image wrapped around the scene, with longitude across it and
+    /// latitude down it. The centre of the image is towards +z.
+    Equirectangular(Canvas),
+    CubeMap(Box<CubeMap>),
+}
+
+impl Background {
+    pub fn color_at(&self, direction: Tuple) -> Tuple {
+        let direction = direction.normalize();
+        match self {
+            Background::Color(color) => *color,
+            Background::Gradient { bottom, top } => {
+                let t = (direction.y + 1.0) / 2.0;
+                *bottom * (1.0 - t) + *top * t
+            }
+            Background::Equirectangular(image) => {
+                let (u, v) = equirectangular_uv(direction);
+                image_at(image, u, v)
+            }
+            Background::CubeMap(cube_map) => cube_map.color_at(direction),
+        }
+    }
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::Color(Tuple::color(0.0, 0.0, 0.0))
+    }
+}
+
+/// Where a unit `direction` falls on an equirectangular image, with `u`
+/// going around from -z and `v` going up from straight down, both from 0
+/// to 1.
+pub fn equirectangular_uv(direction: Tuple) -> (f32, f32) {
+    let theta = direction.x.atan2(direction.z);
+    let phi = direction.y.clamp(-1.0, 1.0).acos();
+    (0.5 + theta / (2.0 * PI), 1.0 - phi / PI)
+}
+
+/// The unit direction that `equirectangular_uv` maps to `(u, v)`.
+pub fn equirectangular_direction(u: f32, v: f32) -> Tuple {
+    let theta = (u - 0.5) * 2.0 * PI;
+    let phi = (1.0 - v) * PI;
+    Tuple::vector(theta.sin() * phi.sin(), phi.cos(), theta.cos() * phi.sin())
+}
+
+/// The pixel nearest to `(u, v)`, where `v` goes up from the bottom row.
+/// An empty image is black everywhere.
+pub fn image_at(image: &Canvas, u: f32, v: f32) -> Tuple {
+    if image.width == 0 || image.height == 0 {
+        return Tuple::color(0.0, 0.0, 0.0);
+    }
+    let x = (u * (image.width - 1) as f32).round();
+    let y = ((1.0 - v) * (image.height - 1) as f32).round();
+    let x = x.clamp(0.0, (image.width - 1) as f32) as u32;
+    let y = y.clamp(0.0, (image.height - 1) as f32) as u32;
+    image
+        .pixel_at(x, y)
+        .cloned()
+        .unwrap_or_else(|| Tuple::color(0.0, 0.0, 0.0))
+}
+
+#[cfg(test)]
+fn solid_image(color: Tuple) -> Canvas {
+    let mut image = Canvas::new(2, 2);
+    for pixel in image.pixels.iter_mut() {
+        *pixel = color;
+    }
+    image
+}
+
+#[test]
+fn test_a_solid_background_is_the_same_in_every_direction() {
+    let color = Tuple::color(0.2, 0.3, 0.4);
+    let background = Background::Color(color);
+    assert_eq!(background.color_at(Tuple::vector(0.0, 1.0, 0.0)), color);
+    assert_eq!(background.color_at(Tuple::vector(1.0, -2.0, 3.0)), color);
+}
+
+#[test]
+fn test_a_gradient_background_blends_from_bottom_to_top() {
+    let background = Background::Gradient {
+        bottom: Tuple::color(1.0, 1.0, 1.0),
+        top: Tuple::color(0.0, 0.0, 1.0),
+    };
+    let examples = [
+        (Tuple::vector(0.0, -1.0, 0.0), Tuple::color(1.0, 1.0, 1.0)),
+        (Tuple::vector(0.0, 0.0, 1.0), Tuple::color(0.5, 0.5, 1.0)),
+        (Tuple::vector(0.0, 3.0, 0.0), Tuple::color(0.0, 0.0, 1.0)),
+    ];
+    for (direction, result) in examples.iter() {
+        assert_eq!(background.color_at(*direction), *result);
+    }
+}
+
+#[test]
+fn test_equirectangular_uv_coordinates() {
+    let examples = [
+        (Tuple::vector(0.0, 0.0, 1.0), (0.5, 0.5)),
+        (Tuple::vector(1.0, 0.0, 0.0), (0.75, 0.5)),
+        (Tuple::vector(-1.0, 0.0, 0.0), (0.25, 0.5)),
+        (Tuple::vector(0.0, 1.0, 0.0), (0.5, 1.0)),
+        (Tuple::vector(0.0, -1.0, 0.0), (0.5, 0.0)),
+    ];
+    for (direction, (u, v)) in examples.iter() {
+        let (actual_u, actual_v) = equirectangular_uv(*direction);
+        assert!(float_eq(actual_u, *u) && float_eq(actual_v, *v));
+    }
+}
+
+#[test]
+fn test_equirectangular_directions_reverse_uv_coordinates() {
+    for (u, v) in [(0.5, 0.5), (0.75, 0.5), (0.1, 0.9), (0.6, 0.2)].iter() {
+        let direction = equirectangular_direction(*u, *v);
+        assert!(float_eq(direction.magnitude(), 1.0));
+        let (actual_u, actual_v) = equirectangular_uv(direction);
+        assert!(float_eq(actual_u, *u) && float_eq(actual_v, *v));
+    }
+}
+
+#[test]
+fn test_looking_up_an_equirectangular_background() {
+    let mut image = Canvas::new(5, 3);
+    let red = Tuple::color(1.0, 0.0, 0.0);
+    let green = Tuple::color(0.0, 1.0, 0.0);
+    let blue = Tuple::color(0.0, 0.0, 1.0);
+    image.write_pixel(2, 1, &red);
+    image.write_pixel(2, 0, &green);
+    image.write_pixel(3, 1, &blue);
+    let background = Background::Equirectangular(image);
+    assert_eq!(background.color_at(Tuple::vector(0.0, 0.0, 1.0)), red);
+    assert_eq!(background.color_at(Tuple::vector(0.0, 1.0, 0.0)), green);
+    assert_eq!(background.color_at(Tuple::vector(1.0, 0.0, 0.0)), blue);
+}
+
+#[test]
+fn test_an_empty_equirectangular_background_is_black() {
+    let background = Background::Equirectangular(Canvas::new(0, 0));
+    assert_eq!(
+        background.color_at(Tuple::vector(0.0, 0.0, 1.0)),
+        Tuple::color(0.0, 0.0, 0.0)
+    );
+}
+
+#[test]
+fn test_a_cube_map_picks_the_face_a_direction_points_at() {
+    let cube_map = CubeMap {
+        left: solid_image(Tuple::color(1.0, 0.0, 0.0)),
+        front: solid_image(Tuple::color(0.0, 1.0, 0.0)),
+        right: solid_image(Tuple::color(0.0, 0.0, 1.0)),
+        back: solid_image(Tuple::color(1.0, 1.0, 0.0)),
+        up: solid_image(Tuple::color(0.0, 1.0, 1.0)),
+        down: solid_image(Tuple::color(1.0, 0.0, 1.0)),
+    };
+    let background = Background::CubeMap(Box::new(cube_map));
+    let examples = [
+        (Tuple::vector(-1.0, 0.5, -0.9), Tuple::color(1.0, 0.0, 0.0)),
+        (Tuple::vector(-0.5, 0.2, 0.9), Tuple::color(0.0, 1.0, 0.0)),
+        (Tuple::vector(1.0, 0.5, -0.9), Tuple::color(0.0, 0.0, 1.0)),
+        (Tuple::vector(-0.5, 0.2, -0.9), Tuple::color(1.0, 1.0, 0.0)),
+        (Tuple::vector(0.3, 1.0, -0.2), Tuple::color(0.0, 1.0, 1.0)),
+        (Tuple::vector(0.3, -1.0, 0.2), Tuple::color(1.0, 0.0, 1.0)),
+    ];
+    for (direction, result) in examples.iter() {
+        assert_eq!(background.color_at(*direction), *result);
+    }
+}
+
+#[test]
+fn test_cube_map_faces_are_oriented_around_the_scene() {
+    let mut front = Canvas::new(2, 2);
+    let red = Tuple::color(1.0, 0.0, 0.0);
+    // Top left, which is up and to the -x side looking along +z
+    front.write_pixel(0, 0, &red);
+    let cube_map = CubeMap {
+        left: Canvas::new(2, 2),
+        front,
+        right: Canvas::new(2, 2),
+        back: Canvas::new(2, 2),
+        up: Canvas::new(2, 2),
+        down: Canvas::new(2, 2),
+    };
+    assert_eq!(cube_map.color_at(Tuple::vector(-0.9, 0.9, 1.0)), red);
+    assert_eq!(
+        cube_map.color_at(Tuple::vector(0.9, 0.9, 1.0)),
+        Tuple::color(0.0, 0.0, 0.0)
+    );
+}
@@ -0,0 +1,426 @@
+use super::EPSILON;
+use matrices::Matrix4;
+use rays::Ray;
+use tuples::Tuple;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingBox {
+    pub min: Tuple,
+    pub max: Tuple,
+}
+
+impl BoundingBox {
+    pub fn new(min: Tuple, max: Tuple) -> Self {
+        BoundingBox { min, max }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x
+            || self.min.y > self.max.y
+            || self.min.z > self.max.z
+    }
+
+    pub fn is_finite(&self) -> bool {
+        [
+            self.min.x, self.min.y, self.min.z, self.max.x, self.max.y,
+            self.max.z,
+        ]
+        .iter()
+        .all(|value| value.is_finite())
+    }
+
+    pub fn centre(&self) -> Tuple {
+        Tuple::point(
+            (self.min.x + self.max.x) / 2.0,
+            (self.min.y + self.max.y) / 2.0,
+            (self.min.z + self.max.z) / 2.0,
+        )
+    }
+
+    pub fn add_point(&mut self, point: Tuple) {
+        self.min = Tuple::point(
+            self.min.x.min(point.x),
+            self.min.y.min(point.y),
+            self.min.z.min(point.z),
+        );
+        self.max = Tuple::point(
+            self.max.x.max(point.x),
+            self.max.y.max(point.y),
+            self.max.z.max(point.z),
+        );
+    }
+
+    pub fn merge(&mut self, other: &BoundingBox) {
+        if !other.is_empty() {
+            self.add_point(other.min);
+            self.add_point(other.max);
+        }
+    }
+
+    pub fn contains_point(&self, point: Tuple) -> bool {
+        self.min.x <= point.x
+            && point.x <= self.max.x
+            && self.min.y <= point.y
+            && point.y <= self.max.y
+            && self.min.z <= point.z
+            && point.z <= self.max.z
+    }
+
+    pub fn contains_box(&self, other: &BoundingBox) -> bool {
+        self.contains_point(other.min) && self.contains_point(other.max)
+    }
+
+    /// The box, in the space `m` transforms into, that fits around this box
+    /// once transformed. Zero terms are skipped so that infinite boxes (such
+    /// as a plane's) stay infinite only along the axes they really extend.
+    pub fn transform(&self, m: Matrix4) -> BoundingBox {
+        if self.is_empty() {
+            return *self;
+        }
+        let min = [self.min.x, self.min.y, self.min.z];
+        let max = [self.max.x, self.max.y, self.max.z];
+        let mut new_min = [0.0; 3];
+        let mut new_max = [0.0; 3];
+        for (i, row) in m.rows.iter().take(3).enumerate() {
+            new_min[i] = row[3];
+            new_max[i] = row[3];
+            for j in 0..3 {
+                if row[j] == 0.0 {
+                    continue;
+                }
+                let a = row[j] * min[j];
+                let b = row[j] * max[j];
+                new_min[i] += a.min(b);
+                new_max[i] += a.max(b);
+            }
+        }
+        BoundingBox::new(
+            Tuple::point(new_min[0], new_min[1], new_min[2]),
+            Tuple::point(new_max[0], new_max[1], new_max[2]),
+        )
+    }
+
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        let (xtmin, xtmax) =
+            check_axis(ray.origin.x, ray.direction.x, self.min.x, self.max.x);
+        let (ytmin, ytmax) =
+            check_axis(ray.origin.y, ray.direction.y, self.min.y, self.max.y);
+        let (ztmin, ztmax) =
+            check_axis(ray.origin.z, ray.direction.z, self.min.z, self.max.z);
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+        tmin <= tmax
+    }
+}
+
+impl Default for BoundingBox {
+    /// An empty box, which grows to fit whatever is added to it.
+    fn default() -> Self {
+        BoundingBox::new(
+            Tuple::point(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            Tuple::point(-f32::INFINITY, -f32::INFINITY, -f32::INFINITY),
+        )
+    }
+}
+
+/// The range of `t` for which a ray is between two planes perpendicular to
+/// one axis.
+pub fn check_axis(
+    origin: f32,
+    direction: f32,
+    min: f32,
+    max: f32,
+) -> (f32, f32) {
+    let tmin_numerator = min - origin;
+    let tmax_numerator = max - origin;
+    let (tmin, tmax) = if direction.abs() >= EPSILON {
+        (tmin_numerator / direction, tmax_numerator / direction)
+    } else {
+        (
+            tmin_numerator * f32::INFINITY,
+            tmax_numerator * f32::INFINITY,
+        )
+    };
+    if tmin > tmax {
+        (tmax, tmin)
+    } else {
+        (tmin, tmax)
+    }
+}
+
+#[test]
+fn test_creating_an_empty_bounding_box() {
+    let bbox = BoundingBox::default();
+    assert!(bbox.is_empty());
+    assert_eq!(bbox.min.x, f32::INFINITY);
+    assert_eq!(bbox.max.x, -f32::INFINITY);
+}
+
+#[test]
+fn test_adding_points_to_an_empty_bounding_box() {
+    let mut bbox = BoundingBox::default();
+    bbox.add_point(Tuple::point(-5.0, 2.0, 0.0));
+    bbox.add_point(Tuple::point(7.0, 0.0, -3.0));
+    assert_eq!(bbox.min, Tuple::point(-5.0, 0.0, -3.0));
+    assert_eq!(bbox.max, Tuple::point(7.0, 2.0, 0.0));
+}
+
+#[test]
+fn test_merging_one_bounding_box_into_another() {
+    let mut box1 = BoundingBox::new(
+        Tuple::point(-5.0, -2.0, 0.0),
+        Tuple::point(7.0, 4.0, 4.0),
+    );
+    let box2 = BoundingBox::new(
+        Tuple::point(8.0, -7.0, -2.0),
+        Tuple::point(14.0, 2.0, 8.0),
+    );
+    box1.merge(&box2);
+    assert_eq!(box1.min, Tuple::point(-5.0, -7.0, -2.0));
+    assert_eq!(box1.max, Tuple::point(14.0, 4.0, 8.0));
+}
+
+#[test]
+fn test_checking_if_a_box_contains_a_point() {
+    let bbox = BoundingBox::new(
+        Tuple::point(5.0, -2.0, 0.0),
+        Tuple::point(11.0, 4.0, 7.0),
+    );
+    let examples = [
+        (Tuple::point(5.0, -2.0, 0.0), true),
+        (Tuple::point(11.0, 4.0, 7.0), true),
+        (Tuple::point(8.0, 1.0, 3.0), true),
+        (Tuple::point(3.0, 0.0, 3.0), false),
+        (Tuple::point(8.0, -4.0, 3.0), false),
+        (Tuple::point(8.0, 1.0, -1.0), false),
+        (Tuple::point(13.0, 1.0, 3.0), false),
+        (Tuple::point(8.0, 5.0, 3.0), false),
+        (Tuple::point(8.0, 1.0, 8.0), false),
+    ];
+    for (point, result) in examples.iter() {
+        assert_eq!(bbox.contains_point(*point), *result);
+    }
+}
+
+#[test]
+fn test_checking_if_a_box_contains_a_box() {
+    let bbox = BoundingBox::new(
+        Tuple::point(5.0, -2.0, 0.0),
+        Tuple::point(11.0, 4.0, 7.0),
+    );
+    let examples = [
+        (
+            Tuple::point(5.0, -2.0, 0.0),
+            Tuple::point(11.0, 4.0, 7.0),
+            true,
+        ),
+        (
+            Tuple::point(6.0, -1.0, 1.0),
+            Tuple::point(10.0, 3.0, 6.0),
+            true,
+        ),
+        (
+            Tuple::point(4.0, -3.0, -1.0),
+            Tuple::point(10.0, 3.0, 6.0),
+            false,
+        ),
+        (
+            Tuple::point(6.0, -1.0, 1.0),
+            Tuple::point(12.0, 5.0, 8.0),
+            false,
+        ),
+    ];
+    for (min, max, result) in examples.iter() {
+        let other = BoundingBox::new(*min, *max);
+        assert_eq!(bbox.contains_box(&other), *result);
+    }
+}
+
+#[test]
+fn test_transforming_a_bounding_box() {
+    use std::f32::consts::PI;
+
+    let bbox = BoundingBox::new(
+        Tuple::point(-1.0, -1.0, -1.0),
+        Tuple::point(1.0, 1.0, 1.0),
+    );
+    let matrix = Matrix4::rotation_x(PI / 4.0) * Matrix4::rotation_y(PI / 4.0);
+    let bbox2 = bbox.transform(matrix);
+    assert_eq!(bbox2.min, Tuple::point(-(2f32.sqrt()), -1.70711, -1.70711));
+    assert_eq!(bbox2.max, Tuple::point(2f32.sqrt(), 1.70711, 1.70711));
+}
+
+#[test]
+fn test_transforming_an_infinite_bounding_box() {
+    let bbox = BoundingBox::new(
+        Tuple::point(-f32::INFINITY, 0.0, -f32::INFINITY),
+        Tuple::point(f32::INFINITY, 0.0, f32::INFINITY),
+    );
+    let bbox2 = bbox.transform(Matrix4::translation(0.0, 2.0, 0.0));
+    assert_eq!(bbox2.min.y, 2.0);
+    assert_eq!(bbox2.max.y, 2.0);
+    assert_eq!(bbox2.min.x, -f32::INFINITY);
+    assert_eq!(bbox2.max.z, f32::INFINITY);
+    assert!(!bbox2.is_finite());
+}
+
+#[test]
+fn test_intersecting_a_ray_with_a_bounding_box_at_the_origin() {
+    let bbox = BoundingBox::new(
+        Tuple::point(-1.0, -1.0, -1.0),
+        Tuple::point(1.0, 1.0, 1.0),
+    );
+    let examples = [
+        (
+            Tuple::point(5.0, 0.5, 0.0),
+            Tuple::vector(-1.0, 0.0, 0.0),
+            true,
+        ),
+        (
+            Tuple::point(-5.0, 0.5, 0.0),
+            Tuple::vector(1.0, 0.0, 0.0),
+            true,
+        ),
+        (
+            Tuple::point(0.5, 5.0, 0.0),
+            Tuple::vector(0.0, -1.0, 0.0),
+            true,
+        ),
+        (
+            Tuple::point(0.5, -5.0, 0.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+            true,
+        ),
+        (
+            Tuple::point(0.5, 0.0, 5.0),
+            Tuple::vector(0.0, 0.0, -1.0),
+            true,
+        ),
+        (
+            Tuple::point(0.5, 0.0, -5.0),
+            Tuple::vector(0.0, 0.0, 1.0),
+            true,
+        ),
+        (
+            Tuple::point(0.0, 0.5, 0.0),
+            Tuple::vector(0.0, 0.0, 1.0),
+            true,
+        ),
+        (
+            Tuple::point(-2.0, 0.0, 0.0),
+            Tuple::vector(2.0, 4.0, 6.0),
+            false,
+        ),
+        (
+            Tuple::point(0.0, -2.0, 0.0),
+            Tuple::vector(6.0, 2.0, 4.0),
+            false,
+        ),
+        (
+            Tuple::point(0.0, 0.0, -2.0),
+            Tuple::vector(4.0, 6.0, 2.0),
+            false,
+        ),
+        (
+            Tuple::point(2.0, 0.0, 2.0),
+            Tuple::vector(0.0, 0.0, -1.0),
+            false,
+        ),
+        (
+            Tuple::point(0.0, 2.0, 2.0),
+            Tuple::vector(0.0, -1.0, 0.0),
+            false,
+        ),
+        (
+            Tuple::point(2.0, 2.0, 0.0),
+            Tuple::vector(-1.0, 0.0, 0.0),
+            false,
+        ),
+    ];
+    for (origin, direction, result) in examples.iter() {
+        let r = Ray::new(*origin, direction.normalize());
+        assert_eq!(bbox.intersects(&r), *result);
+    }
+}
+
+#[test]
+fn test_intersecting_a_ray_with_a_non_cubic_bounding_box() {
+    let bbox = BoundingBox::new(
+        Tuple::point(5.0, -2.0, 0.0),
+        Tuple::point(11.0, 4.0, 7.0),
+    );
+    let examples = [
+        (
+            Tuple::point(15.0, 1.0, 2.0),
+            Tuple::vector(-1.0, 0.0, 0.0),
+            true,
+        ),
+        (
+            Tuple::point(-5.0, -1.0, 4.0),
+            Tuple::vector(1.0, 0.0, 0.0),
+            true,
+        ),
+        (
+            Tuple::point(7.0, 6.0, 5.0),
+            Tuple::vector(0.0, -1.0, 0.0),
+            true,
+        ),
+        (
+            Tuple::point(9.0, -5.0, 6.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+            true,
+        ),
+        (
+            Tuple::point(8.0, 2.0, 12.0),
+            Tuple::vector(0.0, 0.0, -1.0),
+            true,
+        ),
+        (
+            Tuple::point(6.0, 0.0, -5.0),
+            Tuple::vector(0.0, 0.0, 1.0),
+            true,
+        ),
+        (
+            Tuple::point(8.0, 1.0, 3.5),
+            Tuple::vector(0.0, 0.0, 1.0),
+            true,
+        ),
+        (
+            Tuple::point(9.0, -1.0, -8.0),
+            Tuple::vector(2.0, 4.0, 6.0),
+            false,
+        ),
+        (
+            Tuple::point(8.0, 3.0, -4.0),
+            Tuple::vector(6.0, 2.0, 4.0),
+            false,
+        ),
+        (
+            Tuple::point(9.0, -1.0, -2.0),
+            Tuple::vector(4.0, 6.0, 2.0),
+            false,
+        ),
+        (
+            Tuple::point(4.0, 0.0, 9.0),
+            Tuple::vector(0.0, 0.0, -1.0),
+            false,
+        ),
+        (
+            Tuple::point(8.0, 6.0, -1.0),
+            Tuple::vector(0.0, -1.0, 0.0),
+            false,
+        ),
+        (
+            Tuple::point(12.0, 5.0, 4.0),
+            Tuple::vector(-1.0, 0.0, 0.0),
+            false,
+        ),
+    ];
+    for (origin, direction, result) in examples.iter() {
+        let r = Ray::new(*origin, direction.normalize());
+        assert_eq!(bbox.intersects(&r), *result);
+    }
+}
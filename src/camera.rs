@@ -1,23 +1,134 @@
 use super::REFLECTION_RECURSION_LIMIT;
+use background::equirectangular_direction;
 use canvas::Canvas;
+#[cfg(test)]
+use float_eq;
 use matrices::Matrix4;
+use random::{point_in_disk, random};
 use rays::Ray;
+use std::f32::consts::PI;
 use tuples::Tuple;
 use world::World;
+#[cfg(test)]
+use EPSILON;
+
+/// Where within a pixel the samples for anti-aliasing are taken.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Sampling {
+    /// Evenly spaced on a square grid.
+    Grid,
+    /// One at a random place in each cell of a square grid.
+    Jittered,
+    /// Anywhere in the pixel.
+    Random,
+}
+
+impl Sampling {
+    /// Offsets across a pixel, rounding grids up to a square number.
+    pub fn offsets(self, samples: u32) -> Vec<(f32, f32)> {
+        if self == Sampling::Random {
+            return (0..samples.max(1)).map(|_| (random(), random())).collect();
+        }
+        let n = ((samples as f32).sqrt().ceil() as u32).max(1);
+        let cell = 1.0 / n as f32;
+        let mut offsets = Vec::with_capacity((n * n) as usize);
+        for y in 0..n {
+            for x in 0..n {
+                let (jx, jy) = if self == Sampling::Jittered {
+                    (random(), random())
+                } else {
+                    (0.5, 0.5)
+                };
+                offsets.push(((x as f32 + jx) * cell, (y as f32 + jy) * cell));
+            }
+        }
+        offsets
+    }
+}
+
+/// Enough for 4^8 samples in a pixel, well short of overflowing the count.
+const MAX_ADAPTIVE_DEPTH: u32 = 8;
+
+/// Quadruples the samples in pixels that differ by more than `threshold`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AdaptiveSampling {
+    pub threshold: f32,
+    pub max_depth: u32,
+}
+
+impl AdaptiveSampling {
+    pub fn new(threshold: f32, max_depth: u32) -> Self {
+        AdaptiveSampling {
+            threshold,
+            max_depth: max_depth.min(MAX_ADAPTIVE_DEPTH),
+        }
+    }
+}
+
+/// How the camera turns pixels into rays.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    /// Rays spread out from a point, through a plane one unit in front.
+    Perspective,
+    /// Parallel rays, so objects keep their size however far away.
+    Orthographic,
+    /// Every direction, laid out like `Background::Equirectangular`.
+    Equirectangular,
+    /// `field_of_view` spans the inscribed circle; the corners are black.
+    Fisheye,
+}
 
 pub struct Camera {
     hsize: u32,
     vsize: u32,
-    pub field_of_view: f32, // Unused: can we remove this?
+    pub field_of_view: f32,
     pixel_size: f32,
     half_width: f32,
     half_height: f32,
     pub transform: Matrix4,
+    pub projection: Projection,
+    pub samples_per_pixel: u32,
+    pub sampling: Sampling,
+    /// Used instead of `samples_per_pixel` when set.
+    pub adaptive: Option<AdaptiveSampling>,
+    /// Lens radius for perspective cameras; other projections ignore it.
+    pub aperture: f32,
+    /// Where perspective lenses focus; zero or less gives a pinhole.
+    pub focal_distance: f32,
 }
 
 impl Camera {
     pub fn new(hsize: u32, vsize: u32, field_of_view: f32) -> Self {
         let half_view = (field_of_view / 2.0).tan();
+        let mut camera = Camera::with_half_view(hsize, vsize, half_view);
+        camera.field_of_view = field_of_view;
+        camera
+    }
+
+    /// `view_size` is the view's longer side in world units.
+    pub fn orthographic(hsize: u32, vsize: u32, view_size: f32) -> Self {
+        let mut camera = Camera::with_half_view(hsize, vsize, view_size / 2.0);
+        camera.projection = Projection::Orthographic;
+        camera
+    }
+
+    /// A 360° panorama, best on a canvas twice as wide as it is tall.
+    pub fn equirectangular(hsize: u32, vsize: u32) -> Self {
+        let mut camera = Camera::with_half_view(hsize, vsize, 1.0);
+        camera.projection = Projection::Equirectangular;
+        camera.field_of_view = 2.0 * PI;
+        camera
+    }
+
+    /// Sees a hemisphere when `field_of_view` is `PI`.
+    pub fn fisheye(hsize: u32, vsize: u32, field_of_view: f32) -> Self {
+        let mut camera = Camera::with_half_view(hsize, vsize, 1.0);
+        camera.projection = Projection::Fisheye;
+        camera.field_of_view = field_of_view;
+        camera
+    }
+
+    fn with_half_view(hsize: u32, vsize: u32, half_view: f32) -> Self {
         let aspect = hsize as f32 / vsize as f32;
         let half_width: f32;
         let half_height: f32;
@@ -32,43 +143,242 @@ impl Camera {
         Self {
             hsize,
             vsize,
-            field_of_view,
+            field_of_view: 0.0,
             pixel_size,
             half_width,
             half_height,
             transform: Matrix4::default(),
+            projection: Projection::Perspective,
+            samples_per_pixel: 1,
+            sampling: Sampling::Grid,
+            adaptive: None,
+            aperture: 0.0,
+            focal_distance: 1.0,
         }
     }
 
     pub fn ray_for_pixel(&self, px: u32, py: u32) -> Ray {
-        let x_offset = (px as f32 + 0.5) * self.pixel_size;
-        let y_offset = (py as f32 + 0.5) * self.pixel_size;
-        let world_x = self.half_width - x_offset;
-        let world_y = self.half_height - y_offset;
-        let pixel =
-            self.transform.inverse() * Tuple::point(world_x, world_y, -1.0);
-        let origin = self.transform.inverse() * Tuple::point(0.0, 0.0, 0.0);
-        let direction = (pixel - origin).normalize();
-        Ray::new(origin, direction)
+        self.ray_for_pixel_offset(px, py, (0.5, 0.5))
+    }
+
+    /// A ray through `offset`, from 0 to 1 across the pixel from its corner.
+    pub fn ray_for_pixel_offset(
+        &self,
+        px: u32,
+        py: u32,
+        offset: (f32, f32),
+    ) -> Ray {
+        let x = px as f32 + offset.0;
+        let y = py as f32 + offset.1;
+        let inverse = self.transform.inverse();
+        let world_x = self.half_width - x * self.pixel_size;
+        let world_y = self.half_height - y * self.pixel_size;
+        match self.projection {
+            Projection::Perspective
+                if self.aperture <= 0.0 || self.focal_distance <= 0.0 =>
+            {
+                let pixel = inverse * Tuple::point(world_x, world_y, -1.0);
+                let origin = inverse * Tuple::point(0.0, 0.0, 0.0);
+                let direction = (pixel - origin).normalize();
+                Ray::new(origin, direction)
+            }
+            Projection::Perspective => {
+                // Rays from anywhere on the lens meet where the ray through
+                // its centre crosses the focal plane
+                let focal_point = inverse
+                    * Tuple::point(
+                        world_x * self.focal_distance,
+                        world_y * self.focal_distance,
+                        -self.focal_distance,
+                    );
+                let (lens_x, lens_y) = point_in_disk();
+                let origin = inverse
+                    * Tuple::point(
+                        lens_x * self.aperture,
+                        lens_y * self.aperture,
+                        0.0,
+                    );
+                Ray::new(origin, (focal_point - origin).normalize())
+            }
+            Projection::Orthographic => {
+                let origin = inverse * Tuple::point(world_x, world_y, 0.0);
+                let direction = inverse * Tuple::vector(0.0, 0.0, -1.0);
+                Ray::new(origin, direction.normalize())
+            }
+            Projection::Equirectangular => {
+                let direction = equirectangular_direction(
+                    x / self.hsize as f32,
+                    1.0 - y / self.vsize as f32,
+                );
+                let origin = inverse * Tuple::point(0.0, 0.0, 0.0);
+                Ray::new(origin, (inverse * direction).normalize())
+            }
+            Projection::Fisheye => {
+                let (dx, dy, radius) = self.offset_from_centre(x, y);
+                let distance = (dx * dx + dy * dy).sqrt();
+                let theta = distance / radius * self.field_of_view / 2.0;
+                let direction = if distance > 0.0 {
+                    Tuple::vector(
+                        -theta.sin() * dx / distance,
+                        -theta.sin() * dy / distance,
+                        -theta.cos(),
+                    )
+                } else {
+                    Tuple::vector(0.0, 0.0, -1.0)
+                };
+                let origin = inverse * Tuple::point(0.0, 0.0, 0.0);
+                Ray::new(origin, (inverse * direction).normalize())
+            }
+        }
+    }
+
+    /// The offset of `(x, y)` from the centre, and the inscribed radius.
+    fn offset_from_centre(&self, x: f32, y: f32) -> (f32, f32, f32) {
+        let radius = self.hsize.min(self.vsize) as f32 / 2.0;
+        (
+            x - self.hsize as f32 / 2.0,
+            y - self.vsize as f32 / 2.0,
+            radius,
+        )
+    }
+
+    /// False outside a fisheye's inscribed circle.
+    fn sees(&self, px: u32, py: u32, offset: (f32, f32)) -> bool {
+        if self.projection != Projection::Fisheye {
+            return true;
+        }
+        let (dx, dy, radius) =
+            self.offset_from_centre(px as f32 + offset.0, py as f32 + offset.1);
+        dx * dx + dy * dy <= radius * radius
     }
 
     pub fn render(&self, world: World) -> Canvas {
+        if let Some(adaptive) = self.adaptive {
+            return self.render_adaptive(&world, adaptive);
+        }
         let mut canvas = Canvas::new(self.hsize, self.vsize);
         for y in 0..self.vsize {
             for x in 0..self.hsize {
-                let ray = self.ray_for_pixel(x, y);
-                let color = world.color_at(&ray, REFLECTION_RECURSION_LIMIT);
+                let color = self.color_at_pixel(&world, x, y);
                 canvas.write_pixel(x, y, &color);
             }
         }
         canvas
     }
 
+    fn render_adaptive(
+        &self,
+        world: &World,
+        adaptive: AdaptiveSampling,
+    ) -> Canvas {
+        let mut canvas = Canvas::new(self.hsize, self.vsize);
+        let index = |x: u32, y: u32| (y * self.hsize + x) as usize;
+        // The sum of every sample taken in each pixel, and how many
+        let mut totals =
+            vec![(Tuple::color(0.0, 0.0, 0.0), 0); self.num_pixels() as usize];
+        let mut refine = vec![];
+        for y in 0..self.vsize {
+            for x in 0..self.hsize {
+                refine.push((x, y));
+            }
+        }
+        for depth in 0..=adaptive.max_depth.min(MAX_ADAPTIVE_DEPTH) {
+            if depth > 0 {
+                refine.retain(|(x, y)| {
+                    contrast(&canvas, *x, *y) > adaptive.threshold
+                });
+            }
+            if refine.is_empty() {
+                break;
+            }
+            let samples = 4u32.pow(depth);
+            for (x, y) in refine.iter() {
+                let color = self.average_samples(world, *x, *y, samples);
+                let (sum, count) = &mut totals[index(*x, *y)];
+                *sum = *sum + color * samples as f32;
+                *count += samples;
+                canvas.write_pixel(*x, *y, &(*sum / *count as f32));
+            }
+            // Refining a pixel changes how it compares to its neighbours,
+            // so they're checked again at the next level too
+            let mut checked = vec![false; totals.len()];
+            let mut next = vec![];
+            for (x, y) in refine.iter() {
+                let neighbours = [
+                    (*x, *y),
+                    (x.wrapping_sub(1), *y),
+                    (x + 1, *y),
+                    (*x, y.wrapping_sub(1)),
+                    (*x, y + 1),
+                ];
+                for (nx, ny) in neighbours.iter() {
+                    if *nx < self.hsize
+                        && *ny < self.vsize
+                        && !checked[index(*nx, *ny)]
+                    {
+                        checked[index(*nx, *ny)] = true;
+                        next.push((*nx, *ny));
+                    }
+                }
+            }
+            refine = next;
+        }
+        canvas
+    }
+
+    /// The average colour of the pixel's samples.
+    pub fn color_at_pixel(&self, world: &World, px: u32, py: u32) -> Tuple {
+        self.average_samples(world, px, py, self.samples_per_pixel)
+    }
+
+    fn average_samples(
+        &self,
+        world: &World,
+        px: u32,
+        py: u32,
+        samples: u32,
+    ) -> Tuple {
+        let offsets = self.sampling.offsets(samples);
+        let total = offsets
+            .iter()
+            .map(|offset| {
+                if !self.sees(px, py, *offset) {
+                    return Tuple::color(0.0, 0.0, 0.0);
+                }
+                let ray = self.ray_for_pixel_offset(px, py, *offset);
+                world.color_at(&ray, REFLECTION_RECURSION_LIMIT)
+            })
+            .fold(Tuple::color(0.0, 0.0, 0.0), |sum, color| sum + color);
+        total / offsets.len() as f32
+    }
+
     pub fn num_pixels(&self) -> u32 {
         self.vsize * self.hsize
     }
 }
 
+/// The largest channel difference between a pixel and its four neighbours.
+fn contrast(canvas: &Canvas, x: u32, y: u32) -> f32 {
+    let color = canvas.pixel_at(x, y).unwrap();
+    let neighbours = [
+        (x.wrapping_sub(1), y),
+        (x + 1, y),
+        (x, y.wrapping_sub(1)),
+        (x, y + 1),
+    ];
+    neighbours
+        .iter()
+        .filter(|(nx, ny)| *nx < canvas.width && *ny < canvas.height)
+        .map(|(nx, ny)| {
+            let other = canvas.pixel_at(*nx, *ny).unwrap();
+            (color.red() - other.red())
+                .abs()
+                .max((color.green() - other.green()).abs())
+                .max((color.blue() - other.blue()).abs())
+        })
+        .fold(0.0, f32::max)
+}
+
 #[test]
 fn test_constructing_a_camera() {
     use std::f32::consts::PI;
@@ -151,3 +461,354 @@ fn test_rendering_a_world_with_a_camera() {
         Some(&Tuple::color(0.38066, 0.47583, 0.2855))
     );
 }
+
+#[test]
+fn test_a_camera_takes_one_sample_per_pixel_by_default() {
+    use std::f32::consts::PI;
+
+    let camera = Camera::new(160, 120, PI / 2.0);
+    assert_eq!(camera.samples_per_pixel, 1);
+    assert_eq!(camera.sampling, Sampling::Grid);
+    assert_eq!(camera.sampling.offsets(1), vec![(0.5, 0.5)]);
+}
+
+#[test]
+fn test_grid_sampling_offsets() {
+    assert_eq!(
+        Sampling::Grid.offsets(4),
+        vec![(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]
+    );
+    // Rounded up to a square, so asking for more samples never gives fewer
+    assert_eq!(Sampling::Grid.offsets(2).len(), 4);
+    assert_eq!(Sampling::Grid.offsets(10).len(), 16);
+    assert_eq!(Sampling::Jittered.offsets(5).len(), 9);
+}
+
+#[test]
+fn test_jittered_sampling_offsets_stay_in_their_cells() {
+    let offsets = Sampling::Jittered.offsets(4);
+    assert_eq!(offsets.len(), 4);
+    let cells = [(0.0, 0.0), (0.5, 0.0), (0.0, 0.5), (0.5, 0.5)];
+    for ((x, y), (cell_x, cell_y)) in offsets.iter().zip(cells.iter()) {
+        assert!(*x >= *cell_x && *x < cell_x + 0.5);
+        assert!(*y >= *cell_y && *y < cell_y + 0.5);
+    }
+}
+
+#[test]
+fn test_random_sampling_offsets() {
+    let offsets = Sampling::Random.offsets(5);
+    assert_eq!(offsets.len(), 5);
+    for (x, y) in offsets.iter() {
+        assert!((0.0..1.0).contains(x) && (0.0..1.0).contains(y));
+    }
+}
+
+#[test]
+fn test_constructing_a_ray_through_part_of_a_pixel() {
+    use std::f32::consts::PI;
+
+    let camera = Camera::new(201, 101, PI / 2.0);
+    let centre = camera.ray_for_pixel_offset(100, 50, (0.5, 0.5));
+    assert_eq!(centre.direction, camera.ray_for_pixel(100, 50).direction);
+    let corner = camera.ray_for_pixel_offset(100, 50, (0.0, 0.0));
+    let expected = Tuple::vector(1.0 / 201.0, 1.0 / 201.0, -1.0).normalize();
+    assert_eq!(corner.direction, expected);
+}
+
+#[test]
+fn test_supersampling_averages_the_samples_in_a_pixel() {
+    use lighting::PointLight;
+    use shapes::Plane;
+    use std::f32::consts::PI;
+    use transforms::view_transform;
+
+    // Half of the pixel sees a white floor and half sees black sky
+    let mut world = World::new();
+    world.add_light(PointLight::new(
+        Tuple::point(0.0, 10.0, 0.0),
+        Tuple::color(1.0, 1.0, 1.0),
+    ));
+    let mut floor = Plane::new();
+    floor.material.ambient = 1.0;
+    floor.material.diffuse = 0.0;
+    floor.material.specular = 0.0;
+    world.add_shape(floor);
+    let mut camera = Camera::new(1, 1, PI / 2.0);
+    camera.transform = view_transform(
+        Tuple::point(0.0, 1.0, 0.0),
+        Tuple::point(0.0, 1.0, -1.0),
+        Tuple::vector(0.0, 1.0, 0.0),
+    );
+    camera.samples_per_pixel = 4;
+    let color = camera.color_at_pixel(&world, 0, 0);
+    assert_eq!(color, Tuple::color(0.5, 0.5, 0.5));
+}
+
+#[test]
+fn test_contrast_with_neighbouring_pixels() {
+    let mut canvas = Canvas::new(3, 3);
+    canvas.write_pixel(1, 1, &Tuple::color(0.2, 0.5, 0.0));
+    canvas.write_pixel(2, 2, &Tuple::color(1.0, 1.0, 1.0));
+    assert!(float_eq(contrast(&canvas, 1, 1), 0.5));
+    assert!(float_eq(contrast(&canvas, 0, 0), 0.0));
+    assert!(float_eq(contrast(&canvas, 2, 1), 1.0));
+    // Diagonal neighbours don't count
+    assert!(float_eq(contrast(&canvas, 1, 2), 1.0));
+    assert!(float_eq(contrast(&canvas, 0, 2), 0.0));
+}
+
+#[cfg(test)]
+fn horizon_world_and_camera() -> (World, Camera) {
+    use lighting::PointLight;
+    use shapes::Plane;
+    use std::f32::consts::PI;
+    use transforms::view_transform;
+
+    let mut world = World::new();
+    world.add_light(PointLight::new(
+        Tuple::point(0.0, 10.0, 0.0),
+        Tuple::color(1.0, 1.0, 1.0),
+    ));
+    let mut floor = Plane::new();
+    floor.material.ambient = 1.0;
+    floor.material.diffuse = 0.0;
+    floor.material.specular = 0.0;
+    floor.transform = Matrix4::rotation_z(0.3);
+    world.add_shape(floor);
+    let mut camera = Camera::new(9, 9, PI / 2.0);
+    camera.transform = view_transform(
+        Tuple::point(0.0, 1.0, 0.0),
+        Tuple::point(0.0, 1.0, -1.0),
+        Tuple::vector(0.0, 1.0, 0.0),
+    );
+    (world, camera)
+}
+
+#[test]
+fn test_adaptive_sampling_matches_uniform_sampling_along_edges() {
+    let (world, mut camera) = horizon_world_and_camera();
+    camera.samples_per_pixel = 16;
+    let uniform = camera.render(horizon_world_and_camera().0);
+    camera.adaptive = Some(AdaptiveSampling::new(0.1, 2));
+    let adaptive = camera.render(world);
+    let mut refined = 0;
+    for y in 0..9 {
+        for x in 0..9 {
+            let expected = uniform.pixel_at(x, y).unwrap();
+            let actual = adaptive.pixel_at(x, y).unwrap();
+            // Flat pixels need only one sample, while those along the
+            // horizon are refined all the way to 1 + 4 + 16 samples
+            if expected.red() > 0.0 && expected.red() < 1.0 {
+                assert!((actual.red() - expected.red()).abs() < 0.1);
+                refined += 1;
+            } else {
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+    assert!(refined > 0 && refined < 81);
+}
+
+#[test]
+fn test_adaptive_sampling_depth_is_capped() {
+    let adaptive = AdaptiveSampling::new(0.1, 16);
+    assert_eq!(adaptive.max_depth, MAX_ADAPTIVE_DEPTH);
+    assert_eq!(AdaptiveSampling::new(0.1, 2).max_depth, 2);
+}
+
+#[test]
+fn test_adaptive_sampling_keeps_the_samples_from_each_level() {
+    let (world, mut camera) = horizon_world_and_camera();
+    camera.adaptive = Some(AdaptiveSampling::new(0.1, 1));
+    let image = camera.render(horizon_world_and_camera().0);
+    let mut refined = 0;
+    for y in 0..9 {
+        for x in 0..9 {
+            let actual = image.pixel_at(x, y).unwrap();
+            let first = camera.average_samples(&world, x, y, 1);
+            let second = camera.average_samples(&world, x, y, 4);
+            if *actual != first {
+                assert_eq!(*actual, (first + second * 4.0) / 5.0);
+                refined += 1;
+            }
+        }
+    }
+    assert!(refined > 0);
+}
+
+#[test]
+fn test_a_camera_is_a_pinhole_by_default() {
+    use std::f32::consts::PI;
+
+    let camera = Camera::new(201, 101, PI / 2.0);
+    assert_eq!(camera.aperture, 0.0);
+    assert_eq!(camera.focal_distance, 1.0);
+}
+
+#[test]
+fn test_rays_through_a_lens_meet_at_the_focal_plane() {
+    use std::f32::consts::PI;
+    use transforms::view_transform;
+
+    let mut camera = Camera::new(201, 101, PI / 2.0);
+    camera.transform = view_transform(
+        Tuple::point(1.0, 2.0, -5.0),
+        Tuple::point(0.0, 2.0, 0.0),
+        Tuple::vector(0.0, 1.0, 0.0),
+    );
+    let pinhole = camera.ray_for_pixel(20, 30);
+    let forward = camera.ray_for_pixel(100, 50).direction;
+    camera.aperture = 0.5;
+    camera.focal_distance = 4.0;
+    // How far along the pinhole ray the focal plane is
+    let focal_t = 4.0 / pinhole.direction.dot(forward);
+    let focal_point = pinhole.position(focal_t);
+    let mut origins = vec![];
+    for _ in 0..20 {
+        let ray = camera.ray_for_pixel(20, 30);
+        assert!((ray.origin - pinhole.origin).magnitude() <= 0.5 + EPSILON);
+        let to_focus = focal_point - ray.origin;
+        assert!((to_focus.normalize() - ray.direction).magnitude() < 0.0001);
+        origins.push(ray.origin);
+    }
+    assert!(origins.iter().any(|origin| *origin != origins[0]));
+}
+
+#[test]
+fn test_a_lens_without_a_focal_distance_is_a_pinhole() {
+    use std::f32::consts::PI;
+
+    let mut camera = Camera::new(201, 101, PI / 2.0);
+    let pinhole = camera.ray_for_pixel(20, 30);
+    camera.aperture = 0.5;
+    for focal_distance in [0.0, -2.0].iter() {
+        camera.focal_distance = *focal_distance;
+        let ray = camera.ray_for_pixel(20, 30);
+        assert_eq!(ray.origin, pinhole.origin);
+        assert_eq!(ray.direction, pinhole.direction);
+    }
+}
+
+#[test]
+fn test_constructing_an_orthographic_camera() {
+    let camera = Camera::orthographic(200, 100, 10.0);
+    assert_eq!(camera.projection, Projection::Orthographic);
+    assert_eq!(camera.pixel_size, 0.05);
+    assert_eq!(camera.half_width, 5.0);
+    assert_eq!(camera.half_height, 2.5);
+    let portrait = Camera::orthographic(100, 200, 10.0);
+    assert_eq!(portrait.pixel_size, 0.05);
+    assert_eq!(portrait.half_height, 5.0);
+}
+
+#[test]
+fn test_an_orthographic_camera_casts_parallel_rays() {
+    let camera = Camera::orthographic(200, 100, 10.0);
+    let centre = camera.ray_for_pixel(100, 50);
+    assert_eq!(centre.origin, Tuple::point(-0.025, -0.025, 0.0));
+    assert_eq!(centre.direction, Tuple::vector(0.0, 0.0, -1.0));
+    let corner = camera.ray_for_pixel(0, 0);
+    assert_eq!(corner.origin, Tuple::point(4.975, 2.475, 0.0));
+    assert_eq!(corner.direction, Tuple::vector(0.0, 0.0, -1.0));
+}
+
+#[test]
+fn test_an_orthographic_camera_when_transformed() {
+    use std::f32::consts::PI;
+
+    let mut camera = Camera::orthographic(201, 101, 10.0);
+    camera.transform =
+        Matrix4::rotation_y(PI / 4.0) * Matrix4::translation(0.0, -2.0, 5.0);
+    let ray = camera.ray_for_pixel(100, 50);
+    assert_eq!(ray.origin, Tuple::point(0.0, 2.0, -5.0));
+    assert_eq!(
+        ray.direction,
+        Tuple::vector(2f32.sqrt() / 2.0, 0.0, -(2f32.sqrt()) / 2.0)
+    );
+}
+
+#[test]
+fn test_an_equirectangular_camera_sees_all_around() {
+    let camera = Camera::equirectangular(360, 180);
+    let examples = [
+        (180, 90, (0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0)),
+        (90, 90, (0.0, 0.0), Tuple::vector(-1.0, 0.0, 0.0)),
+        (270, 90, (0.0, 0.0), Tuple::vector(1.0, 0.0, 0.0)),
+        (0, 90, (0.0, 0.0), Tuple::vector(0.0, 0.0, -1.0)),
+        (180, 0, (0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0)),
+        (180, 179, (0.0, 1.0), Tuple::vector(0.0, -1.0, 0.0)),
+    ];
+    for (px, py, offset, direction) in examples.iter() {
+        let ray = camera.ray_for_pixel_offset(*px, *py, *offset);
+        assert_eq!(ray.origin, Tuple::point(0.0, 0.0, 0.0));
+        assert!((ray.direction - *direction).magnitude() < 0.0001);
+    }
+}
+
+#[test]
+fn test_an_equirectangular_render_lines_up_with_the_background() {
+    use background::equirectangular_uv;
+
+    let camera = Camera::equirectangular(64, 32);
+    for (px, py) in [(5, 3), (32, 16), (60, 20), (17, 30)].iter() {
+        let ray = camera.ray_for_pixel(*px, *py);
+        let (u, v) = equirectangular_uv(ray.direction);
+        assert!((u - (*px as f32 + 0.5) / 64.0).abs() < 0.0001);
+        assert!((v - (1.0 - (*py as f32 + 0.5) / 32.0)).abs() < 0.0001);
+    }
+}
+
+#[test]
+fn test_a_fisheye_camera_sees_a_hemisphere() {
+    let camera = Camera::fisheye(200, 100, PI);
+    assert_eq!(camera.projection, Projection::Fisheye);
+    assert_eq!(camera.field_of_view, PI);
+    let examples = [
+        (100, 50, Tuple::vector(0.0, 0.0, -1.0)),
+        (50, 50, Tuple::vector(1.0, 0.0, 0.0)),
+        (150, 50, Tuple::vector(-1.0, 0.0, 0.0)),
+        (100, 0, Tuple::vector(0.0, 1.0, 0.0)),
+        (
+            75,
+            50,
+            Tuple::vector(2f32.sqrt() / 2.0, 0.0, -(2f32.sqrt()) / 2.0),
+        ),
+    ];
+    for (px, py, direction) in examples.iter() {
+        let ray = camera.ray_for_pixel_offset(*px, *py, (0.0, 0.0));
+        assert_eq!(ray.origin, Tuple::point(0.0, 0.0, 0.0));
+        assert!((ray.direction - *direction).magnitude() < 0.0001);
+    }
+}
+
+#[test]
+fn test_a_fisheye_camera_when_transformed() {
+    let mut camera = Camera::fisheye(101, 101, PI);
+    camera.transform =
+        Matrix4::rotation_y(PI / 2.0) * Matrix4::translation(0.0, -2.0, 5.0);
+    let ray = camera.ray_for_pixel(50, 50);
+    assert_eq!(ray.origin, Tuple::point(0.0, 2.0, -5.0));
+    assert_eq!(ray.direction, Tuple::vector(1.0, 0.0, 0.0));
+}
+
+#[test]
+fn test_a_fisheye_camera_sees_nothing_outside_its_circle() {
+    use background::Background;
+
+    let mut world = World::new();
+    world.background = Background::Color(Tuple::color(1.0, 1.0, 1.0));
+    let camera = Camera::fisheye(11, 11, PI);
+    assert_eq!(
+        camera.color_at_pixel(&world, 0, 0),
+        Tuple::color(0.0, 0.0, 0.0)
+    );
+    assert_eq!(
+        camera.color_at_pixel(&world, 5, 5),
+        Tuple::color(1.0, 1.0, 1.0)
+    );
+    assert_eq!(
+        camera.color_at_pixel(&world, 5, 0),
+        Tuple::color(1.0, 1.0, 1.0)
+    );
+}
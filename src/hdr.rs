@@ -0,0 +1,213 @@
+use canvas::Canvas;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{Error, ErrorKind, Result};
+use tuples::Tuple;
+
+/// Reads a Radiance `.hdr` (RGBE) image into a canvas of unclamped colours.
+pub fn from_file(filename: &str) -> Result<Canvas> {
+    let mut file = File::open(filename)?;
+    let mut contents = vec![];
+    file.read_to_end(&mut contents)?;
+    parse(&contents)
+}
+
+pub fn parse(bytes: &[u8]) -> Result<Canvas> {
+    let mut reader = Reader { bytes, position: 0 };
+    let magic = reader.line()?;
+    if !magic.starts_with("#?") {
+        return Err(invalid("not a Radiance HDR file"));
+    }
+    loop {
+        let line = reader.line()?;
+        if line.is_empty() {
+            break;
+        }
+        if line.starts_with("FORMAT=") && line != "FORMAT=32-bit_rle_rgbe" {
+            return Err(invalid("only RGBE pixels are supported"));
+        }
+    }
+    let resolution = reader.line()?;
+    let (flip, height, width) =
+        match resolution.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [y, height, "+X", width] if *y == "-Y" || *y == "+Y" => {
+                (*y == "+Y", parse_size(height)?, parse_size(width)?)
+            }
+            _ => return Err(invalid("unsupported image orientation")),
+        };
+    let mut canvas = Canvas::new(width, height);
+    let mut scanline = vec![[0u8; 4]; width as usize];
+    for row in 0..height {
+        reader.scanline(&mut scanline)?;
+        let y = if flip { height - 1 - row } else { row };
+        for (x, rgbe) in scanline.iter().enumerate() {
+            canvas.write_pixel(x as u32, y, &rgbe_to_color(*rgbe));
+        }
+    }
+    Ok(canvas)
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn parse_size(size: &str) -> Result<u32> {
+    size.parse().map_err(|_| invalid("invalid image size"))
+}
+
+/// Each colour channel shares the exponent in the fourth byte.
+fn rgbe_to_color(rgbe: [u8; 4]) -> Tuple {
+    if rgbe[3] == 0 {
+        return Tuple::color(0.0, 0.0, 0.0);
+    }
+    let scale = 2f32.powi(rgbe[3] as i32 - (128 + 8));
+    Tuple::color(
+        rgbe[0] as f32 * scale,
+        rgbe[1] as f32 * scale,
+        rgbe[2] as f32 * scale,
+    )
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8> {
+        let byte =
+            self.bytes.get(self.position).cloned().ok_or_else(|| {
+                Error::new(ErrorKind::UnexpectedEof, "truncated")
+            })?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn line(&mut self) -> Result<String> {
+        let mut line = vec![];
+        loop {
+            match self.byte()? {
+                b'\n' => break,
+                byte => line.push(byte),
+            }
+        }
+        Ok(String::from_utf8_lossy(&line).trim().to_string())
+    }
+
+    /// Flat RGBE pixels, or run-length encoded after a `2 2` prefix.
+    fn scanline(&mut self, scanline: &mut [[u8; 4]]) -> Result<()> {
+        let width = scanline.len();
+        let start = self.position;
+        let prefix = [self.byte()?, self.byte()?, self.byte()?, self.byte()?];
+        let encoded_width = ((prefix[2] as usize) << 8) | prefix[3] as usize;
+        let is_rle = (8..0x8000).contains(&width)
+            && prefix[0] == 2
+            && prefix[1] == 2
+            && prefix[2] & 0x80 == 0;
+        if !is_rle {
+            self.position = start;
+            for pixel in scanline.iter_mut() {
+                for channel in pixel.iter_mut() {
+                    *channel = self.byte()?;
+                }
+            }
+            return Ok(());
+        }
+        if encoded_width != width {
+            return Err(invalid("scanline width doesn't match the image"));
+        }
+        for channel in 0..4 {
+            let mut x = 0;
+            while x < width {
+                let count = self.byte()? as usize;
+                if count > 128 {
+                    let count = count - 128;
+                    let value = self.byte()?;
+                    if x + count > width {
+                        return Err(invalid("run overflows the scanline"));
+                    }
+                    for pixel in scanline[x..x + count].iter_mut() {
+                        pixel[channel] = value;
+                    }
+                    x += count;
+                } else {
+                    if count == 0 || x + count > width {
+                        return Err(invalid("invalid run length"));
+                    }
+                    for pixel in scanline[x..x + count].iter_mut() {
+                        pixel[channel] = self.byte()?;
+                    }
+                    x += count;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn header(resolution: &str) -> Vec<u8> {
+    format!(
+        "#?RADIANCE\n# made by hand\nFORMAT=32-bit_rle_rgbe\n\n{}\n",
+        resolution
+    )
+    .into_bytes()
+}
+
+#[test]
+fn test_converting_rgbe_pixels_to_colors() {
+    assert_eq!(rgbe_to_color([0, 0, 0, 0]), Tuple::color(0.0, 0.0, 0.0));
+    assert_eq!(
+        rgbe_to_color([128, 64, 0, 129]),
+        Tuple::color(1.0, 0.5, 0.0)
+    );
+    assert_eq!(
+        rgbe_to_color([128, 192, 32, 131]),
+        Tuple::color(4.0, 6.0, 1.0)
+    );
+}
+
+#[test]
+fn test_parsing_a_flat_hdr_image() {
+    let mut bytes = header("-Y 2 +X 2");
+    bytes.extend_from_slice(&[128, 0, 0, 129, 0, 128, 0, 129]);
+    bytes.extend_from_slice(&[0, 0, 128, 129, 128, 128, 128, 131]);
+    let canvas = parse(&bytes).unwrap();
+    assert_eq!((canvas.width, canvas.height), (2, 2));
+    assert_eq!(canvas.pixel_at(0, 0), Some(&Tuple::color(1.0, 0.0, 0.0)));
+    assert_eq!(canvas.pixel_at(1, 0), Some(&Tuple::color(0.0, 1.0, 0.0)));
+    assert_eq!(canvas.pixel_at(0, 1), Some(&Tuple::color(0.0, 0.0, 1.0)));
+    assert_eq!(canvas.pixel_at(1, 1), Some(&Tuple::color(4.0, 4.0, 4.0)));
+}
+
+#[test]
+fn test_parsing_a_run_length_encoded_hdr_image() {
+    let mut bytes = header("+Y 2 +X 8");
+    for value in [64u8, 128].iter() {
+        bytes.extend_from_slice(&[2, 2, 0, 8]);
+        // Red is a run, green is literal values, blue and the exponent are
+        // runs split in two
+        bytes.extend_from_slice(&[128 + 8, *value]);
+        bytes.extend_from_slice(&[8, 0, 0, 0, 0, 128, 128, 128, 128]);
+        bytes.extend_from_slice(&[128 + 4, 0, 128 + 4, 0]);
+        bytes.extend_from_slice(&[128 + 3, 129, 128 + 5, 129]);
+    }
+    let canvas = parse(&bytes).unwrap();
+    assert_eq!((canvas.width, canvas.height), (8, 2));
+    // +Y stores the bottom row first
+    assert_eq!(canvas.pixel_at(0, 1), Some(&Tuple::color(0.5, 0.0, 0.0)));
+    assert_eq!(canvas.pixel_at(7, 1), Some(&Tuple::color(0.5, 1.0, 0.0)));
+    assert_eq!(canvas.pixel_at(0, 0), Some(&Tuple::color(1.0, 0.0, 0.0)));
+    assert_eq!(canvas.pixel_at(7, 0), Some(&Tuple::color(1.0, 1.0, 0.0)));
+}
+
+#[test]
+fn test_rejecting_invalid_hdr_files() {
+    assert!(parse(b"P3\n1 1\n255\n0 0 0\n").is_err());
+    let mut truncated = header("-Y 2 +X 2");
+    truncated.extend_from_slice(&[128, 0, 0, 129]);
+    assert!(parse(&truncated).is_err());
+    let mut xyz = b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n".to_vec();
+    xyz.extend_from_slice(&[0, 0, 0, 0]);
+    assert!(parse(&xyz).is_err());
+}
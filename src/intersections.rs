@@ -1,5 +1,5 @@
 use super::{float_eq, EPSILON};
-use lighting::lighting;
+use lighting::{ambient, lighting_samples};
 use rays::Ray;
 use shapes::Shape;
 use std::cmp;
@@ -10,12 +10,17 @@ use world::World;
 pub struct Intersection {
     pub t: f32,
     pub object: Shape,
+    pub u: f32,
+    pub v: f32,
     pub point: Option<Tuple>,
     pub over_point: Option<Tuple>,
+    pub under_point: Option<Tuple>,
     pub eyev: Option<Tuple>,
     pub normalv: Option<Tuple>,
     pub inside: Option<bool>,
     pub reflectv: Option<Tuple>,
+    pub n1: Option<f32>,
+    pub n2: Option<f32>,
 }
 
 impl PartialEq for Intersection {
@@ -52,23 +57,45 @@ impl cmp::Ord for Intersection {
 
 impl Intersection {
     pub fn new(t: f32, object: Shape) -> Self {
+        Intersection::new_with_uv(t, object, 0.0, 0.0)
+    }
+
+    /// An intersection that also records where on the surface it happened,
+    /// as barycentric coordinates for triangles.
+    pub fn new_with_uv(t: f32, object: Shape, u: f32, v: f32) -> Self {
         Intersection {
             t,
             object,
+            u,
+            v,
             eyev: None,
             normalv: None,
             point: None,
             over_point: None,
+            under_point: None,
             inside: None,
             reflectv: None,
+            n1: None,
+            n2: None,
         }
     }
 
     pub fn prepare_hit(&mut self, ray: &Ray) {
-        let mut position = ray.position(self.t);
+        self.prepare_hit_with_intersections(ray, &[]);
+    }
+
+    /// Prepares the hit as `prepare_hit` does, also working out the
+    /// refractive indices either side of the surface from the other
+    /// intersections along the ray, which must be sorted by `t`.
+    pub fn prepare_hit_with_intersections(
+        &mut self,
+        ray: &Ray,
+        xs: &[Intersection],
+    ) {
+        let surface = ray.position(self.t);
         let eyev = -ray.direction;
-        let normalv = self.object.normal_at(position);
-        position = position + normalv * 0.0001;
+        let normalv = self.object.normal_at_hit(surface, self);
+        let position = surface + normalv * 0.0001;
         self.eyev = Some(eyev);
         self.point = Some(position);
         if normalv.dot(eyev) < 0.0 {
@@ -80,9 +107,61 @@ impl Intersection {
         }
         self.over_point =
             Some(self.point.unwrap() + self.normalv.unwrap() * EPSILON);
+        self.under_point = Some(surface - self.normalv.unwrap() * 0.0001);
         if let Some(normalv) = self.normalv {
             self.reflectv = Some(ray.direction.reflect(normalv));
         }
+        let (n1, n2) = self.refractive_indices(xs);
+        self.n1 = Some(n1);
+        self.n2 = Some(n2);
+    }
+
+    fn refractive_indices(&self, xs: &[Intersection]) -> (f32, f32) {
+        let mut n1 = 1.0;
+        let mut n2 = 1.0;
+        let mut containers: Vec<&Shape> = vec![];
+        for i in xs {
+            let is_hit = i == self;
+            if is_hit {
+                n1 = containers
+                    .last()
+                    .map(|object| object.material.refractive_index)
+                    .unwrap_or(1.0);
+            }
+            if let Some(idx) =
+                containers.iter().position(|object| **object == i.object)
+            {
+                containers.remove(idx);
+            } else {
+                containers.push(&i.object);
+            }
+            if is_hit {
+                n2 = containers
+                    .last()
+                    .map(|object| object.material.refractive_index)
+                    .unwrap_or(1.0);
+                break;
+            }
+        }
+        (n1, n2)
+    }
+
+    /// The Schlick approximation of the Fresnel effect: how much light is
+    /// reflected rather than refracted at this hit.
+    pub fn schlick(&self) -> f32 {
+        let n1 = self.n1.unwrap();
+        let n2 = self.n2.unwrap();
+        let mut cos = self.eyev.unwrap().dot(self.normalv.unwrap());
+        if n1 > n2 {
+            let n = n1 / n2;
+            let sin2_t = n.powi(2) * (1.0 - cos.powi(2));
+            if sin2_t > 1.0 {
+                return 1.0;
+            }
+            cos = (1.0 - sin2_t).sqrt();
+        }
+        let r0 = ((n1 - n2) / (n1 + n2)).powi(2);
+        r0 + (1.0 - r0) * (1.0 - cos).powi(5)
     }
 
     pub fn reflected_color(&self, world: &World, remaining: i32) -> Tuple {
@@ -96,19 +175,82 @@ impl Intersection {
         }
     }
 
+    pub fn refracted_color(&self, world: &World, remaining: i32) -> Tuple {
+        let black = Tuple::color(0.0, 0.0, 0.0);
+        if remaining <= 0 || self.object.material.transparency == 0.0 {
+            return black;
+        }
+        let n_ratio = self.n1.unwrap() / self.n2.unwrap();
+        let eyev = self.eyev.unwrap();
+        let normalv = self.normalv.unwrap();
+        let cos_i = eyev.dot(normalv);
+        let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
+        if sin2_t > 1.0 {
+            // Total internal reflection
+            return black;
+        }
+        let cos_t = (1.0 - sin2_t).sqrt();
+        let direction = normalv * (n_ratio * cos_i - cos_t) - eyev * n_ratio;
+        let refract_ray = Ray::new(self.under_point.unwrap(), direction);
+        world.color_at(&refract_ray, remaining - 1)
+            * self.object.material.transparency
+    }
+
     pub fn shade_hit(&self, world: &World, remaining: i32) -> Tuple {
-        let is_shadowed = world.is_shadowed(self.over_point.unwrap());
-        let surface = lighting(
-            self.object.material,
-            self.object,
-            world.light_source.unwrap(),
+        let mut material = self.object.material;
+        // Occlusion rays are only worth casting when there's ambient light
+        if material.ambient > 0.0 {
+            material.ambient *= world.ambient_occlusion_at(
+                self.over_point.unwrap(),
+                self.normalv.unwrap(),
+            );
+        }
+        // Each light adds its own ambient term, so adding a light never
+        // makes a surface darker
+        let ambient_light = world
+            .lights
+            .iter()
+            .fold(Tuple::color(0.0, 0.0, 0.0), |sum, light| {
+                sum + light.intensity()
+            });
+        let ambient = ambient(
+            material,
+            &self.object,
             self.over_point.unwrap(),
-            self.eyev.unwrap(),
-            self.normalv.unwrap(),
-            is_shadowed,
+            ambient_light,
         );
+        let mut direct = material;
+        direct.ambient = 0.0;
+        let surface = world
+            .lights
+            .iter()
+            .map(|light| {
+                let point = self.over_point.unwrap();
+                let samples = if self.object.receives_shadow {
+                    world.light_samples(*light, point)
+                } else {
+                    light.light_samples(point)
+                };
+                lighting_samples(
+                    direct,
+                    &self.object,
+                    *light,
+                    point,
+                    self.eyev.unwrap(),
+                    self.normalv.unwrap(),
+                    &samples,
+                )
+            })
+            .fold(ambient, |sum, color| sum + color)
+            + world.environment_lighting(self);
         let reflected = self.reflected_color(world, remaining);
-        surface + reflected
+        let refracted = self.refracted_color(world, remaining);
+        if material.reflective > 0.0 && material.transparency > 0.0 {
+            let reflectance = self.schlick();
+            surface + reflected * reflectance + refracted * (1.0 - reflectance)
+        } else {
+            surface + reflected + refracted
+        }
     }
 }
 
@@ -168,6 +310,67 @@ fn test_the_point_is_offset() {
     assert!(hit.point.unwrap().z > hit.over_point.unwrap().z);
 }
 
+#[test]
+fn test_shade_hit_sums_the_contribution_of_every_light() {
+    let mut world = World::default();
+    let ray =
+        Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+    let shape = world.objects[0].clone();
+    let mut hit = Intersection::new(4.0, shape);
+    hit.prepare_hit(&ray);
+    let one_light = hit.shade_hit(&world, REFLECTION_RECURSION_LIMIT);
+    world.add_light(world.lights[0]);
+    let two_lights = hit.shade_hit(&world, REFLECTION_RECURSION_LIMIT);
+    assert_eq!(two_lights, one_light * 2.0);
+}
+
+#[test]
+fn test_shade_hit_tests_shadows_separately_for_each_light() {
+    let mut world = World::new();
+    world.add_light(PointLight::new(
+        Tuple::point(0.0, 0.0, -10.0),
+        Tuple::color(1.0, 1.0, 1.0),
+    ));
+    world.add_light(PointLight::new(
+        Tuple::point(0.0, 0.0, 20.0),
+        Tuple::color(0.5, 0.5, 0.5),
+    ));
+    world.add_shape(Sphere::new());
+    let mut s2 = Sphere::new();
+    s2.transform = Matrix4::translation(0.0, 0.0, 10.0);
+    world.add_shape(s2.clone());
+    let ray =
+        Ray::new(Tuple::point(0.0, 0.0, 5.0), Tuple::vector(0.0, 0.0, 1.0));
+    let mut hit = Intersection::new(4.0, s2);
+    hit.prepare_hit(&ray);
+    let c = hit.shade_hit(&world, REFLECTION_RECURSION_LIMIT);
+    // The first light is blocked and the second is behind the surface,
+    // leaving only the ambient term from each of them
+    assert_eq!(c, Tuple::color(0.15, 0.15, 0.15));
+}
+
+#[test]
+fn test_adding_a_dimmer_light_never_darkens_a_surface() {
+    let mut world = World::new();
+    world.add_shape(Sphere::new());
+    let ray =
+        Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+    let mut hit = Intersection::new(4.0, world.objects[0].clone());
+    hit.prepare_hit(&ray);
+    world.add_light(PointLight::new(
+        Tuple::point(0.0, 0.0, -10.0),
+        Tuple::color(1.0, 1.0, 1.0),
+    ));
+    let key = hit.shade_hit(&world, REFLECTION_RECURSION_LIMIT);
+    // A fill light behind the sphere only adds its ambient term
+    world.add_light(PointLight::new(
+        Tuple::point(0.0, 0.0, 10.0),
+        Tuple::color(0.2, 0.2, 0.2),
+    ));
+    let key_and_fill = hit.shade_hit(&world, REFLECTION_RECURSION_LIMIT);
+    assert_eq!(key_and_fill, key + Tuple::color(0.02, 0.02, 0.02));
+}
+
 #[test]
 fn test_precomputing_the_reflection_vector() {
     let shape = Plane::new();
@@ -204,7 +407,7 @@ fn test_the_reflected_color_for_a_reflective_material() {
     let mut shape = Plane::new();
     shape.material.reflective = 0.5;
     shape.transform = Matrix4::translation(0.0, -1.0, 0.0);
-    world.add_shape(shape);
+    world.add_shape(shape.clone());
     let ray = Ray::new(
         Tuple::point(0.0, 0.0, -3.0),
         Tuple::vector(0.0, -(2f32.sqrt()) / 2.0, 2f32.sqrt() / 2.0),
@@ -223,7 +426,7 @@ fn test_the_reflected_color_at_the_maximum_recursive_depth() {
     let mut shape = Plane::new();
     shape.material.reflective = 0.5;
     shape.transform = Matrix4::translation(0.0, -1.0, 0.0);
-    world.add_shape(shape);
+    world.add_shape(shape.clone());
     let ray = Ray::new(
         Tuple::point(0.0, 0.0, -3.0),
         Tuple::vector(0.0, -2f32.sqrt() / 2.0, 2f32.sqrt() / 2.0),
@@ -239,7 +442,7 @@ fn test_shade_hit_with_a_reflective_material() {
     let mut shape = Plane::new();
     shape.material.reflective = 0.5;
     shape.transform = Matrix4::translation(0.0, -1.0, 0.0);
-    world.add_shape(shape);
+    world.add_shape(shape.clone());
     let ray = Ray::new(
         Tuple::point(0.0, 0.0, -3.0),
         Tuple::vector(0.0, -(2f32.sqrt()) / 2.0, 2f32.sqrt() / 2.0),
@@ -265,10 +468,11 @@ fn test_shading_an_intersection() {
 #[test]
 fn test_shading_an_intersection_from_the_inside() {
     let mut world = World::default();
-    world.light_source = Some(PointLight::new(
+    world.lights = vec![PointLight::new(
         Tuple::point(0.0, 0.25, 0.0),
         Tuple::color(1.0, 1.0, 1.0),
-    ));
+    )
+    .into()];
     let ray =
         Ray::new(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0));
     let shape = world.objects[1].clone();
@@ -281,15 +485,16 @@ fn test_shading_an_intersection_from_the_inside() {
 #[test]
 fn test_when_shade_hit_is_given_an_intersection_in_shadow() {
     let mut world = World::new();
-    world.light_source = Some(PointLight::new(
+    world.lights = vec![PointLight::new(
         Tuple::point(0.0, 0.0, -10.0),
         Tuple::color(1.0, 1.0, 1.0),
-    ));
+    )
+    .into()];
     let s1 = Sphere::new();
     world.objects.push(s1);
     let mut s2 = Sphere::new();
     s2.transform = Matrix4::translation(0.0, 0.0, 10.0);
-    world.objects.push(s2);
+    world.objects.push(s2.clone());
     let ray =
         Ray::new(Tuple::point(0.0, 0.0, 5.0), Tuple::vector(0.0, 0.0, 1.0));
     let mut hit = Intersection::new(4.0, s2);
@@ -298,6 +503,258 @@ fn test_when_shade_hit_is_given_an_intersection_in_shadow() {
     assert_eq!(c, Tuple::color(0.1, 0.1, 0.1));
 }
 
+#[test]
+fn test_shade_hit_ignores_shadows_on_objects_that_dont_receive_them() {
+    let mut world = World::new();
+    world.add_light(PointLight::new(
+        Tuple::point(0.0, 0.0, -10.0),
+        Tuple::color(1.0, 1.0, 1.0),
+    ));
+    world.add_shape(Sphere::new());
+    let mut s2 = Sphere::new();
+    s2.transform = Matrix4::translation(0.0, 0.0, 10.0);
+    s2.receives_shadow = false;
+    world.add_shape(s2.clone());
+    let ray =
+        Ray::new(Tuple::point(0.0, 0.0, 5.0), Tuple::vector(0.0, 0.0, 1.0));
+    let mut hit = Intersection::new(4.0, s2);
+    hit.prepare_hit(&ray);
+    let c = hit.shade_hit(&world, REFLECTION_RECURSION_LIMIT);
+    assert_eq!(c, Tuple::color(1.9, 1.9, 1.9));
+}
+
+#[cfg(test)]
+fn glass_sphere() -> Shape {
+    let mut sphere = Sphere::new();
+    sphere.material.transparency = 1.0;
+    sphere.material.refractive_index = 1.5;
+    sphere
+}
+
+#[test]
+fn test_finding_n1_and_n2_at_various_intersections() {
+    let mut a = glass_sphere();
+    a.transform = Matrix4::scaling(2.0, 2.0, 2.0);
+    a.material.refractive_index = 1.5;
+    let mut b = glass_sphere();
+    b.transform = Matrix4::translation(0.0, 0.0, -0.25);
+    b.material.refractive_index = 2.0;
+    let mut c = glass_sphere();
+    c.transform = Matrix4::translation(0.0, 0.0, 0.25);
+    c.material.refractive_index = 2.5;
+    let ray =
+        Ray::new(Tuple::point(0.0, 0.0, -4.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = vec![
+        Intersection::new(2.0, a.clone()),
+        Intersection::new(2.75, b.clone()),
+        Intersection::new(3.25, c.clone()),
+        Intersection::new(4.75, b),
+        Intersection::new(5.25, c),
+        Intersection::new(6.0, a),
+    ];
+    let expected = [
+        (1.0, 1.5),
+        (1.5, 2.0),
+        (2.0, 2.5),
+        (2.5, 2.5),
+        (2.5, 1.5),
+        (1.5, 1.0),
+    ];
+    for (index, (n1, n2)) in expected.iter().enumerate() {
+        let mut hit = xs[index].clone();
+        hit.prepare_hit_with_intersections(&ray, &xs);
+        assert_eq!(hit.n1, Some(*n1));
+        assert_eq!(hit.n2, Some(*n2));
+    }
+}
+
+#[test]
+fn test_the_under_point_is_offset_below_the_surface() {
+    let ray =
+        Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+    let mut shape = glass_sphere();
+    shape.transform = Matrix4::translation(0.0, 0.0, 1.0);
+    let xs = vec![Intersection::new(5.0, shape)];
+    let mut hit = xs[0].clone();
+    hit.prepare_hit_with_intersections(&ray, &xs);
+    assert!(hit.under_point.unwrap().z > EPSILON / 2.0);
+    assert!(hit.point.unwrap().z < hit.under_point.unwrap().z);
+}
+
+#[test]
+fn test_the_refracted_color_with_an_opaque_surface() {
+    let world = World::default();
+    let shape = world.objects[0].clone();
+    let ray =
+        Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = vec![
+        Intersection::new(4.0, shape.clone()),
+        Intersection::new(6.0, shape),
+    ];
+    let mut hit = xs[0].clone();
+    hit.prepare_hit_with_intersections(&ray, &xs);
+    assert_eq!(hit.refracted_color(&world, 5), Tuple::color(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn test_the_refracted_color_at_the_maximum_recursive_depth() {
+    let mut world = World::default();
+    world.objects[0].material.transparency = 1.0;
+    world.objects[0].material.refractive_index = 1.5;
+    let shape = world.objects[0].clone();
+    let ray =
+        Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = vec![
+        Intersection::new(4.0, shape.clone()),
+        Intersection::new(6.0, shape),
+    ];
+    let mut hit = xs[0].clone();
+    hit.prepare_hit_with_intersections(&ray, &xs);
+    assert_eq!(hit.refracted_color(&world, 0), Tuple::color(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn test_the_refracted_color_under_total_internal_reflection() {
+    let mut world = World::default();
+    world.objects[0].material.transparency = 1.0;
+    world.objects[0].material.refractive_index = 1.5;
+    let shape = world.objects[0].clone();
+    let ray = Ray::new(
+        Tuple::point(0.0, 0.0, 2f32.sqrt() / 2.0),
+        Tuple::vector(0.0, 1.0, 0.0),
+    );
+    let xs = vec![
+        Intersection::new(-(2f32.sqrt()) / 2.0, shape.clone()),
+        Intersection::new(2f32.sqrt() / 2.0, shape),
+    ];
+    let mut hit = xs[1].clone();
+    hit.prepare_hit_with_intersections(&ray, &xs);
+    assert_eq!(hit.refracted_color(&world, 5), Tuple::color(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn test_the_refracted_color_with_a_refracted_ray() {
+    use patterns::{Pattern, PatternKind};
+
+    let mut world = World::default();
+    world.objects[0].material.ambient = 1.0;
+    world.objects[0].material.pattern = Some(Pattern {
+        kind: PatternKind::TestPattern,
+        ..Pattern::default()
+    });
+    world.objects[1].material.transparency = 1.0;
+    world.objects[1].material.refractive_index = 1.5;
+    let a = world.objects[0].clone();
+    let b = world.objects[1].clone();
+    let ray =
+        Ray::new(Tuple::point(0.0, 0.0, 0.1), Tuple::vector(0.0, 1.0, 0.0));
+    let xs = vec![
+        Intersection::new(-0.9899, a.clone()),
+        Intersection::new(-0.4899, b.clone()),
+        Intersection::new(0.4899, b),
+        Intersection::new(0.9899, a),
+    ];
+    let mut hit = xs[2].clone();
+    hit.prepare_hit_with_intersections(&ray, &xs);
+    let color = hit.refracted_color(&world, 5);
+    // The surface offsets in prepare_hit shift this slightly from the
+    // 0.99888 an unoffset hit would give
+    assert_eq!(color, Tuple::color(0.0, 0.99897, 0.04725));
+}
+
+#[test]
+fn test_shade_hit_with_a_transparent_material() {
+    let mut world = World::default();
+    let mut floor = Plane::new();
+    floor.transform = Matrix4::translation(0.0, -1.0, 0.0);
+    floor.material.transparency = 0.5;
+    floor.material.refractive_index = 1.5;
+    world.add_shape(floor.clone());
+    let mut ball = Sphere::new();
+    ball.material.color = Tuple::color(1.0, 0.0, 0.0);
+    ball.material.ambient = 0.5;
+    ball.transform = Matrix4::translation(0.0, -3.5, -0.5);
+    world.add_shape(ball);
+    let ray = Ray::new(
+        Tuple::point(0.0, 0.0, -3.0),
+        Tuple::vector(0.0, -(2f32.sqrt()) / 2.0, 2f32.sqrt() / 2.0),
+    );
+    let xs = vec![Intersection::new(2f32.sqrt(), floor)];
+    let mut hit = xs[0].clone();
+    hit.prepare_hit_with_intersections(&ray, &xs);
+    let color = hit.shade_hit(&world, 5);
+    // Light reaches the ball through the transparent floor, so it's a
+    // brighter red than the 0.93642 it would be under a solid shadow
+    assert_eq!(color, Tuple::color(1.12546, 0.68642, 0.68642));
+}
+
+#[test]
+fn test_the_schlick_approximation_under_total_internal_reflection() {
+    let shape = glass_sphere();
+    let ray = Ray::new(
+        Tuple::point(0.0, 0.0, 2f32.sqrt() / 2.0),
+        Tuple::vector(0.0, 1.0, 0.0),
+    );
+    let xs = vec![
+        Intersection::new(-(2f32.sqrt()) / 2.0, shape.clone()),
+        Intersection::new(2f32.sqrt() / 2.0, shape),
+    ];
+    let mut hit = xs[1].clone();
+    hit.prepare_hit_with_intersections(&ray, &xs);
+    assert_eq!(hit.schlick(), 1.0);
+}
+
+#[test]
+fn test_the_schlick_approximation_with_a_perpendicular_viewing_angle() {
+    let shape = glass_sphere();
+    let ray =
+        Ray::new(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0));
+    let xs = vec![
+        Intersection::new(-1.0, shape.clone()),
+        Intersection::new(1.0, shape),
+    ];
+    let mut hit = xs[1].clone();
+    hit.prepare_hit_with_intersections(&ray, &xs);
+    assert!(float_eq(hit.schlick(), 0.04));
+}
+
+#[test]
+fn test_the_schlick_approximation_with_small_angle_and_n2_greater_than_n1() {
+    let shape = glass_sphere();
+    let ray =
+        Ray::new(Tuple::point(0.0, 0.99, -2.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = vec![Intersection::new(1.8589, shape)];
+    let mut hit = xs[0].clone();
+    hit.prepare_hit_with_intersections(&ray, &xs);
+    assert!((hit.schlick() - 0.48873).abs() < 0.0001);
+}
+
+#[test]
+fn test_shade_hit_with_a_reflective_transparent_material() {
+    let mut world = World::default();
+    let mut floor = Plane::new();
+    floor.transform = Matrix4::translation(0.0, -1.0, 0.0);
+    floor.material.reflective = 0.5;
+    floor.material.transparency = 0.5;
+    floor.material.refractive_index = 1.5;
+    world.add_shape(floor.clone());
+    let mut ball = Sphere::new();
+    ball.material.color = Tuple::color(1.0, 0.0, 0.0);
+    ball.material.ambient = 0.5;
+    ball.transform = Matrix4::translation(0.0, -3.5, -0.5);
+    world.add_shape(ball);
+    let ray = Ray::new(
+        Tuple::point(0.0, 0.0, -3.0),
+        Tuple::vector(0.0, -(2f32.sqrt()) / 2.0, 2f32.sqrt() / 2.0),
+    );
+    let xs = vec![Intersection::new(2f32.sqrt(), floor)];
+    let mut hit = xs[0].clone();
+    hit.prepare_hit_with_intersections(&ray, &xs);
+    let color = hit.shade_hit(&world, 5);
+    // As above, the ball is lit through the floor rather than in shadow
+    assert_eq!(color, Tuple::color(1.115, 0.69643, 0.69243));
+}
+
 pub fn find_hit<'a>(intersections: &[Intersection]) -> Option<Intersection> {
     intersections
         .iter()
@@ -314,6 +771,18 @@ fn test_an_intersection_encapsulates_t_and_object() {
     assert_eq!(i.object, s);
 }
 
+#[test]
+fn test_an_intersection_can_encapsulate_u_and_v() {
+    let s = Triangle::new(
+        Tuple::point(0.0, 1.0, 0.0),
+        Tuple::point(-1.0, 0.0, 0.0),
+        Tuple::point(1.0, 0.0, 0.0),
+    );
+    let i = Intersection::new_with_uv(3.5, s, 0.2, 0.4);
+    assert_eq!(i.u, 0.2);
+    assert_eq!(i.v, 0.4);
+}
+
 #[test]
 fn test_aggregating_intersections() {
     let s = Sphere::new();
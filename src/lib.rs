@@ -1,10 +1,15 @@
+pub mod background;
+pub mod bounds;
 pub mod camera;
 pub mod canvas;
+pub mod hdr;
 pub mod intersections;
 pub mod lighting;
 pub mod materials;
 pub mod matrices;
+pub mod obj;
 pub mod patterns;
+pub mod random;
 pub mod rays;
 pub mod shapes;
 pub mod transforms;
@@ -1,12 +1,57 @@
+#[cfg(test)]
+use float_eq;
 use materials::Material;
 use patterns::pattern_at_shape;
+use random::random;
 use shapes::Shape;
 use tuples::Tuple;
 
+/// Divides a light by `constant + linear * d + quadratic * d * d`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Attenuation {
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Attenuation {
+    pub fn new(constant: f32, linear: f32, quadratic: f32) -> Self {
+        Attenuation {
+            constant,
+            linear,
+            quadratic,
+        }
+    }
+
+    /// Physically correct falloff, at full intensity one unit away.
+    pub fn inverse_square() -> Self {
+        Attenuation::new(0.0, 0.0, 1.0)
+    }
+
+    /// Never more than 1.0, so a light can't blow out what's next to it.
+    pub fn factor(&self, distance: f32) -> f32 {
+        let falloff = self.constant
+            + self.linear * distance
+            + self.quadratic * distance * distance;
+        if falloff <= 1.0 {
+            1.0
+        } else {
+            1.0 / falloff
+        }
+    }
+}
+
+impl Default for Attenuation {
+    fn default() -> Self {
+        Attenuation::new(1.0, 0.0, 0.0)
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct PointLight {
     pub position: Tuple,
     pub intensity: Tuple,
+    pub attenuation: Attenuation,
 }
 
 impl PointLight {
@@ -14,6 +59,7 @@ impl PointLight {
         PointLight {
             position,
             intensity,
+            attenuation: Attenuation::default(),
         }
     }
 }
@@ -25,46 +71,578 @@ fn test_a_point_light_has_a_position_and_intensity() {
     let light = PointLight::new(position, intensity);
     assert_eq!(light.intensity, intensity);
     assert_eq!(light.position, position);
+    assert_eq!(light.attenuation, Attenuation::new(1.0, 0.0, 0.0));
+}
+
+#[test]
+fn test_attenuation_factors() {
+    let examples = [
+        (Attenuation::default(), 10.0, 1.0),
+        (Attenuation::inverse_square(), 1.0, 1.0),
+        (Attenuation::inverse_square(), 2.0, 0.25),
+        (Attenuation::new(1.0, 0.5, 0.0), 2.0, 0.5),
+        (Attenuation::new(1.0, 0.0, 0.25), 2.0, 0.5),
+    ];
+    for (attenuation, distance, result) in examples.iter() {
+        assert!(float_eq(attenuation.factor(*distance), *result));
+    }
+}
+
+#[test]
+fn test_attenuation_never_brightens_a_light() {
+    let examples = [
+        (Attenuation::inverse_square(), 0.0),
+        (Attenuation::inverse_square(), 0.001),
+        (Attenuation::new(0.0, 1.0, 0.0), 0.5),
+        (Attenuation::new(0.0, 0.0, 0.0), 0.0),
+        (Attenuation::new(0.0, 0.0, 0.0), 10.0),
+    ];
+    for (attenuation, distance) in examples.iter() {
+        assert_eq!(attenuation.factor(*distance), 1.0);
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AreaLight {
+    pub corner: Tuple,
+    pub uvec: Tuple,
+    pub usteps: u32,
+    pub vvec: Tuple,
+    pub vsteps: u32,
+    pub position: Tuple,
+    pub intensity: Tuple,
+    pub jitter: bool,
+}
+
+impl AreaLight {
+    /// A `usteps` by `vsteps` grid of cells, with at least one each way.
+    pub fn new(
+        corner: Tuple,
+        full_uvec: Tuple,
+        usteps: u32,
+        full_vvec: Tuple,
+        vsteps: u32,
+        intensity: Tuple,
+    ) -> Self {
+        let usteps = usteps.max(1);
+        let vsteps = vsteps.max(1);
+        AreaLight {
+            corner,
+            uvec: full_uvec / usteps as f32,
+            usteps,
+            vvec: full_vvec / vsteps as f32,
+            vsteps,
+            position: corner + full_uvec / 2.0 + full_vvec / 2.0,
+            intensity,
+            jitter: false,
+        }
+    }
+
+    /// The centre of cell `(u, v)`, or a random point in it when jittered.
+    pub fn point_on_light(&self, u: u32, v: u32) -> Tuple {
+        let (ju, jv) = if self.jitter {
+            (random(), random())
+        } else {
+            (0.5, 0.5)
+        };
+        self.corner + self.uvec * (u as f32 + ju) + self.vvec * (v as f32 + jv)
+    }
+}
+
+/// The direction and distance to a light sample, and how much gets through.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LightSample {
+    pub direction: Tuple,
+    pub distance: f32,
+    pub intensity: Tuple,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpotLight {
+    pub position: Tuple,
+    pub direction: Tuple,
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+    pub intensity: Tuple,
+}
+
+impl SpotLight {
+    /// Fully lit within `inner_angle`, fading out by `outer_angle` radians.
+    pub fn new(
+        position: Tuple,
+        direction: Tuple,
+        inner_angle: f32,
+        outer_angle: f32,
+        intensity: Tuple,
+    ) -> Self {
+        SpotLight {
+            position,
+            direction: direction.normalize(),
+            inner_angle,
+            outer_angle,
+            intensity,
+        }
+    }
+
+    /// From 0.0 outside the cone to 1.0 inside the inner cone.
+    pub fn spot_factor(&self, point: Tuple) -> f32 {
+        let cos_angle = (point - self.position).normalize().dot(self.direction);
+        let cos_inner = self.inner_angle.cos();
+        let cos_outer = self.outer_angle.cos();
+        if cos_angle >= cos_inner {
+            1.0
+        } else if cos_angle <= cos_outer {
+            0.0
+        } else {
+            let t = (cos_angle - cos_outer) / (cos_inner - cos_outer);
+            t * t * (3.0 - 2.0 * t)
+        }
+    }
+}
+
+/// A light infinitely far away, shining along `direction` everywhere.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DirectionalLight {
+    pub direction: Tuple,
+    pub intensity: Tuple,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Tuple, intensity: Tuple) -> Self {
+        DirectionalLight {
+            direction: direction.normalize(),
+            intensity,
+        }
+    }
+}
+
+/// Lights the scene from the background, in `samples` random directions.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EnvironmentLight {
+    pub samples: u32,
+    pub intensity: Tuple,
+}
+
+impl EnvironmentLight {
+    pub fn new(samples: u32) -> Self {
+        EnvironmentLight {
+            samples,
+            intensity: Tuple::color(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+/// Darkens ambient light where rays are blocked within `distance`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AmbientOcclusion {
+    pub samples: u32,
+    pub distance: f32,
+}
+
+impl AmbientOcclusion {
+    pub fn new(samples: u32, distance: f32) -> Self {
+        AmbientOcclusion { samples, distance }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Light {
+    Point(PointLight),
+    Area(AreaLight),
+    Spot(SpotLight),
+    Directional(DirectionalLight),
+}
+
+impl Light {
+    pub fn intensity(&self) -> Tuple {
+        match *self {
+            Light::Point(light) => light.intensity,
+            Light::Area(light) => light.intensity,
+            Light::Spot(light) => light.intensity,
+            Light::Directional(light) => light.intensity,
+        }
+    }
+
+    /// Where the light is, or `None` for a directional light.
+    pub fn position(&self) -> Option<Tuple> {
+        match *self {
+            Light::Point(light) => Some(light.position),
+            Light::Area(light) => Some(light.position),
+            Light::Spot(light) => Some(light.position),
+            Light::Directional(_) => None,
+        }
+    }
+
+    /// The points shading and shadows sample, none for directional lights.
+    pub fn samples(&self) -> Vec<Tuple> {
+        match *self {
+            Light::Point(light) => vec![light.position],
+            Light::Spot(light) => vec![light.position],
+            Light::Directional(_) => vec![],
+            Light::Area(light) => {
+                let mut samples = vec![];
+                for v in 0..light.vsteps {
+                    for u in 0..light.usteps {
+                        samples.push(light.point_on_light(u, v));
+                    }
+                }
+                samples
+            }
+        }
+    }
+
+    /// Each sample seen from `point`, as if nothing were in the way.
+    pub fn light_samples(&self, point: Tuple) -> Vec<LightSample> {
+        let white = Tuple::color(1.0, 1.0, 1.0);
+        match *self {
+            Light::Directional(light) => vec![LightSample {
+                direction: -light.direction,
+                distance: f32::INFINITY,
+                intensity: white,
+            }],
+            _ => self
+                .samples()
+                .iter()
+                .map(|sample| {
+                    let v = *sample - point;
+                    LightSample {
+                        direction: v.normalize(),
+                        distance: v.magnitude(),
+                        intensity: white,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// How much the light has faded after travelling `distance`.
+    pub fn attenuation(&self, distance: f32) -> f32 {
+        match *self {
+            Light::Point(light) => light.attenuation.factor(distance),
+            _ => 1.0,
+        }
+    }
+
+    /// How much of the light is directed towards `point`.
+    pub fn spot_factor(&self, point: Tuple) -> f32 {
+        match *self {
+            Light::Spot(light) => light.spot_factor(point),
+            _ => 1.0,
+        }
+    }
+}
+
+impl From<PointLight> for Light {
+    fn from(light: PointLight) -> Self {
+        Light::Point(light)
+    }
+}
+
+impl From<AreaLight> for Light {
+    fn from(light: AreaLight) -> Self {
+        Light::Area(light)
+    }
+}
+
+impl From<SpotLight> for Light {
+    fn from(light: SpotLight) -> Self {
+        Light::Spot(light)
+    }
+}
+
+impl From<DirectionalLight> for Light {
+    fn from(light: DirectionalLight) -> Self {
+        Light::Directional(light)
+    }
+}
+
+#[test]
+fn test_creating_an_area_light() {
+    let corner = Tuple::point(0.0, 0.0, 0.0);
+    let v1 = Tuple::vector(2.0, 0.0, 0.0);
+    let v2 = Tuple::vector(0.0, 0.0, 1.0);
+    let light =
+        AreaLight::new(corner, v1, 4, v2, 2, Tuple::color(1.0, 1.0, 1.0));
+    assert_eq!(light.corner, corner);
+    assert_eq!(light.uvec, Tuple::vector(0.5, 0.0, 0.0));
+    assert_eq!(light.usteps, 4);
+    assert_eq!(light.vvec, Tuple::vector(0.0, 0.0, 0.5));
+    assert_eq!(light.vsteps, 2);
+    assert_eq!(Light::from(light).samples().len(), 8);
+    assert_eq!(light.position, Tuple::point(1.0, 0.0, 0.5));
+}
+
+#[test]
+fn test_an_area_light_has_at_least_one_cell_each_way() {
+    let corner = Tuple::point(0.0, 0.0, 0.0);
+    let v1 = Tuple::vector(2.0, 0.0, 0.0);
+    let v2 = Tuple::vector(0.0, 0.0, 1.0);
+    let light =
+        AreaLight::new(corner, v1, 0, v2, 0, Tuple::color(1.0, 1.0, 1.0));
+    assert_eq!(light.uvec, v1);
+    assert_eq!(light.vvec, v2);
+    assert_eq!(Light::from(light).samples(), vec![light.position]);
+}
+
+#[test]
+fn test_finding_a_single_point_on_an_area_light() {
+    let corner = Tuple::point(0.0, 0.0, 0.0);
+    let v1 = Tuple::vector(2.0, 0.0, 0.0);
+    let v2 = Tuple::vector(0.0, 0.0, 1.0);
+    let light =
+        AreaLight::new(corner, v1, 4, v2, 2, Tuple::color(1.0, 1.0, 1.0));
+    let examples = [
+        (0, 0, Tuple::point(0.25, 0.0, 0.25)),
+        (1, 0, Tuple::point(0.75, 0.0, 0.25)),
+        (0, 1, Tuple::point(0.25, 0.0, 0.75)),
+        (2, 0, Tuple::point(1.25, 0.0, 0.25)),
+        (3, 1, Tuple::point(1.75, 0.0, 0.75)),
+    ];
+    for (u, v, result) in examples.iter() {
+        assert_eq!(light.point_on_light(*u, *v), *result);
+    }
 }
 
+#[test]
+fn test_a_jittered_point_stays_inside_its_cell() {
+    let corner = Tuple::point(0.0, 0.0, 0.0);
+    let v1 = Tuple::vector(2.0, 0.0, 0.0);
+    let v2 = Tuple::vector(0.0, 0.0, 1.0);
+    let mut light =
+        AreaLight::new(corner, v1, 4, v2, 2, Tuple::color(1.0, 1.0, 1.0));
+    light.jitter = true;
+    for _ in 0..100 {
+        let p = light.point_on_light(3, 1);
+        assert!(p.x >= 1.5 && p.x <= 2.0);
+        assert!(p.z >= 0.5 && p.z <= 1.0);
+        assert_eq!(p.y, 0.0);
+    }
+}
+
+#[test]
+fn test_creating_a_spotlight() {
+    let light = SpotLight::new(
+        Tuple::point(0.0, 10.0, 0.0),
+        Tuple::vector(0.0, -2.0, 0.0),
+        0.2,
+        0.4,
+        Tuple::color(1.0, 1.0, 1.0),
+    );
+    assert_eq!(light.direction, Tuple::vector(0.0, -1.0, 0.0));
+    assert_eq!(Light::from(light).samples(), vec![light.position]);
+}
+
+#[test]
+fn test_the_spot_factor_falls_off_between_the_cone_angles() {
+    use std::f32::consts::PI;
+
+    let light = SpotLight::new(
+        Tuple::point(0.0, 0.0, 0.0),
+        Tuple::vector(0.0, 0.0, 1.0),
+        PI / 6.0,
+        PI / 3.0,
+        Tuple::color(1.0, 1.0, 1.0),
+    );
+    let examples = [
+        (Tuple::point(0.0, 0.0, 10.0), 1.0),
+        (Tuple::point(0.0, 1.0, 2.0), 1.0),
+        (Tuple::point(0.0, 2.0, 1.0), 0.0),
+        (Tuple::point(0.0, 0.0, -10.0), 0.0),
+    ];
+    for (point, result) in examples.iter() {
+        assert!(float_eq(light.spot_factor(*point), *result));
+    }
+    let halfway = light.spot_factor(Tuple::point(0.0, 1.0, 1.0));
+    let further_out = light.spot_factor(Tuple::point(0.0, 1.2, 1.0));
+    assert!(halfway > further_out && further_out > 0.0 && halfway < 1.0);
+}
+
+#[test]
+fn test_lighting_is_scaled_by_the_spot_factor() {
+    use std::f32::consts::PI;
+
+    let m = Material::default();
+    let object = Shape::default();
+    let position = Tuple::point(0.0, 0.0, 0.0);
+    let eyev = Tuple::vector(0.0, 0.0, -1.0);
+    let normalv = Tuple::vector(0.0, 0.0, -1.0);
+    let aimed_at = SpotLight::new(
+        Tuple::point(0.0, 0.0, -10.0),
+        Tuple::vector(0.0, 0.0, 1.0),
+        PI / 8.0,
+        PI / 4.0,
+        Tuple::color(1.0, 1.0, 1.0),
+    );
+    let result = lighting(
+        m,
+        &object,
+        aimed_at.into(),
+        position,
+        eyev,
+        normalv,
+        Tuple::color(1.0, 1.0, 1.0),
+    );
+    assert_eq!(result, Tuple::color(1.9, 1.9, 1.9));
+    let aimed_away = SpotLight::new(
+        Tuple::point(0.0, 0.0, -10.0),
+        Tuple::vector(0.0, 1.0, 0.0),
+        PI / 8.0,
+        PI / 4.0,
+        Tuple::color(1.0, 1.0, 1.0),
+    );
+    let result = lighting(
+        m,
+        &object,
+        aimed_away.into(),
+        position,
+        eyev,
+        normalv,
+        Tuple::color(1.0, 1.0, 1.0),
+    );
+    assert_eq!(result, Tuple::color(0.1, 0.1, 0.1));
+}
+
+#[test]
+fn test_a_directional_light_has_the_same_light_vector_everywhere() {
+    let light = Light::from(DirectionalLight::new(
+        Tuple::vector(0.0, -2.0, 0.0),
+        Tuple::color(1.0, 1.0, 1.0),
+    ));
+    assert_eq!(light.position(), None);
+    for point in [
+        Tuple::point(0.0, 0.0, 0.0),
+        Tuple::point(100.0, -5.0, 3.0),
+        Tuple::point(-1e6, 1e6, 0.0),
+    ]
+    .iter()
+    {
+        let samples = light.light_samples(*point);
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].direction, Tuple::vector(0.0, 1.0, 0.0));
+    }
+}
+
+#[test]
+fn test_lighting_with_a_directional_light_at_an_angle() {
+    let m = Material::default();
+    let object = Shape::default();
+    let position = Tuple::point(0.0, 0.0, 0.0);
+    let eyev = Tuple::vector(0.0, 0.0, -1.0);
+    let normalv = Tuple::vector(0.0, 0.0, -1.0);
+    let light = DirectionalLight::new(
+        Tuple::vector(0.0, -1.0, 1.0),
+        Tuple::color(1.0, 1.0, 1.0),
+    );
+    let result = lighting(
+        m,
+        &object,
+        light.into(),
+        position,
+        eyev,
+        normalv,
+        Tuple::color(1.0, 1.0, 1.0),
+    );
+    assert_eq!(result, Tuple::color(0.7364, 0.7364, 0.7364));
+}
+
+#[test]
+fn test_lighting_with_an_attenuated_point_light() {
+    let m = Material::default();
+    let object = Shape::default();
+    let position = Tuple::point(0.0, 0.0, 0.0);
+    let eyev = Tuple::vector(0.0, 0.0, -1.0);
+    let normalv = Tuple::vector(0.0, 0.0, -1.0);
+    let mut light = PointLight::new(
+        Tuple::point(0.0, 0.0, -2.0),
+        Tuple::color(1.0, 1.0, 1.0),
+    );
+    light.attenuation = Attenuation::inverse_square();
+    let result = lighting(
+        m,
+        &object,
+        light.into(),
+        position,
+        eyev,
+        normalv,
+        Tuple::color(1.0, 1.0, 1.0),
+    );
+    // The ambient term isn't affected, diffuse and specular are quartered
+    assert_eq!(result, Tuple::color(0.55, 0.55, 0.55));
+}
+
+fn surface_color(material: Material, object: &Shape, point: Tuple) -> Tuple {
+    material
+        .pattern
+        .map(|pattern| pattern_at_shape(pattern, object, point))
+        .unwrap_or(material.color)
+}
+
+/// The light a surface reflects from `intensity` regardless of direction.
+pub fn ambient(
+    material: Material,
+    object: &Shape,
+    point: Tuple,
+    intensity: Tuple,
+) -> Tuple {
+    surface_color(material, object, point) * intensity * material.ambient
+}
+
+/// Shades a point with `intensity` from `World::intensity_at`.
 pub fn lighting(
     material: Material,
-    object: Shape,
-    light: PointLight,
+    object: &Shape,
+    light: Light,
     point: Tuple,
     eyev: Tuple,
     normalv: Tuple,
-    in_shadow: bool,
+    intensity: Tuple,
+) -> Tuple {
+    let samples = light
+        .light_samples(point)
+        .into_iter()
+        .map(|sample| LightSample {
+            intensity,
+            ..sample
+        })
+        .collect::<Vec<_>>();
+    lighting_samples(material, object, light, point, eyev, normalv, &samples)
+}
+
+/// Like `lighting`, with the light's samples already drawn.
+pub fn lighting_samples(
+    material: Material,
+    object: &Shape,
+    light: Light,
+    point: Tuple,
+    eyev: Tuple,
+    normalv: Tuple,
+    samples: &[LightSample],
 ) -> Tuple {
     let black = Tuple::color(0.0, 0.0, 0.0);
-    let diffuse;
-    let specular;
-    let color = material
-        .pattern
-        .map(|pattern| pattern_at_shape(pattern, object, point))
-        .unwrap_or(material.color);
-    let effective_color = color * light.intensity;
-    let lightv = (light.position - point).normalize();
+    let effective_color =
+        surface_color(material, object, point) * light.intensity();
     let ambient = effective_color * material.ambient;
-    let light_dot_normal = lightv.dot(normalv);
-    if light_dot_normal < 0.0 {
-        diffuse = black;
-        specular = black;
-    } else {
-        diffuse = effective_color * material.diffuse * light_dot_normal;
+    let mut sum = black;
+    for sample in samples.iter() {
+        let lightv = sample.direction;
+        let light_dot_normal = lightv.dot(normalv);
+        if light_dot_normal < 0.0 {
+            continue;
+        }
+        let diffuse = effective_color * material.diffuse * light_dot_normal;
         let reflectv = -lightv.reflect(normalv);
         let reflect_dot_eye = reflectv.dot(eyev).powf(material.shininess);
-        specular = if reflect_dot_eye <= 0.0 {
+        let specular = if reflect_dot_eye <= 0.0 {
             black
         } else {
-            light.intensity * material.specular * reflect_dot_eye
+            light.intensity() * material.specular * reflect_dot_eye
         };
+        sum = sum
+            + (diffuse + specular)
+                * sample.intensity
+                * light.attenuation(sample.distance);
     }
-    if in_shadow {
-        ambient
-    } else {
-        ambient + diffuse + specular
-    }
+    ambient + sum / samples.len() as f32 * light.spot_factor(point)
 }
 
 #[test]
@@ -78,8 +656,9 @@ fn test_lighting_with_the_eye_between_the_light_and_the_surface() {
         Tuple::point(0.0, 0.0, -10.0),
         Tuple::color(1.0, 1.0, 1.0),
     );
-    let in_shadow = false;
-    let result = lighting(m, object, light, position, eyev, normalv, in_shadow);
+    let intensity = Tuple::color(1.0, 1.0, 1.0);
+    let result =
+        lighting(m, &object, light.into(), position, eyev, normalv, intensity);
     assert_eq!(result, Tuple::color(1.9, 1.9, 1.9));
 }
 
@@ -95,8 +674,9 @@ fn test_lighting_with_the_eye_between_light_and_surface_eye_offset_45_degrees()
         Tuple::point(0.0, 0.0, -10.0),
         Tuple::color(1.0, 1.0, 1.0),
     );
-    let in_shadow = false;
-    let result = lighting(m, object, light, position, eyev, normalv, in_shadow);
+    let intensity = Tuple::color(1.0, 1.0, 1.0);
+    let result =
+        lighting(m, &object, light.into(), position, eyev, normalv, intensity);
     assert_eq!(result, Tuple::color(1.0, 1.0, 1.0));
 }
 
@@ -111,8 +691,9 @@ fn test_lighting_with_eye_opposite_surface_light_offset_45_degrees() {
         Tuple::point(0.0, 10.0, -10.0),
         Tuple::color(1.0, 1.0, 1.0),
     );
-    let in_shadow = false;
-    let result = lighting(m, object, light, position, eyev, normalv, in_shadow);
+    let intensity = Tuple::color(1.0, 1.0, 1.0);
+    let result =
+        lighting(m, &object, light.into(), position, eyev, normalv, intensity);
     assert_eq!(result, Tuple::color(0.7364, 0.7364, 0.7364));
 }
 
@@ -127,8 +708,9 @@ fn test_lighting_with_eye_in_the_path_of_the_reflection_vector() {
         Tuple::point(0.0, 10.0, -10.0),
         Tuple::color(1.0, 1.0, 1.0),
     );
-    let in_shadow = false;
-    let result = lighting(m, object, light, position, eyev, normalv, in_shadow);
+    let intensity = Tuple::color(1.0, 1.0, 1.0);
+    let result =
+        lighting(m, &object, light.into(), position, eyev, normalv, intensity);
     assert_eq!(result, Tuple::color(1.63638, 1.63638, 1.63638));
 }
 
@@ -143,8 +725,9 @@ fn test_lighting_with_the_light_behind_the_surface() {
         Tuple::point(0.0, 0.0, 10.0),
         Tuple::color(1.0, 1.0, 1.0),
     );
-    let in_shadow = false;
-    let result = lighting(m, object, light, position, eyev, normalv, in_shadow);
+    let intensity = Tuple::color(1.0, 1.0, 1.0);
+    let result =
+        lighting(m, &object, light.into(), position, eyev, normalv, intensity);
     assert_eq!(result, Tuple::color(0.1, 0.1, 0.1));
 }
 
@@ -159,8 +742,9 @@ fn test_lighting_with_the_surface_in_shadow() {
         Tuple::point(0.0, 0.0, -10.0),
         Tuple::color(1.0, 1.0, 1.0),
     );
-    let in_shadow = true;
-    let result = lighting(m, object, light, position, eyev, normalv, in_shadow);
+    let intensity = Tuple::color(0.0, 0.0, 0.0);
+    let result =
+        lighting(m, &object, light.into(), position, eyev, normalv, intensity);
     assert_eq!(result, Tuple::color(0.1, 0.1, 0.1));
 }
 
@@ -186,22 +770,98 @@ fn test_lighting_with_a_pattern_applied() {
     );
     let c1 = lighting(
         m,
-        object,
-        light,
+        &object,
+        light.into(),
         Tuple::point(0.9, 0.0, 0.0),
         eyev,
         normalv,
-        false,
+        Tuple::color(1.0, 1.0, 1.0),
     );
     let c2 = lighting(
         m,
-        object,
-        light,
+        &object,
+        light.into(),
         Tuple::point(1.0, 0.0, 0.0),
         eyev,
         normalv,
-        false,
+        Tuple::color(1.0, 1.0, 1.0),
     );
     assert_eq!(c1, Tuple::color(1.0, 1.0, 1.0));
     assert_eq!(c2, Tuple::color(0.0, 0.0, 0.0));
 }
+
+#[test]
+fn test_lighting_uses_light_intensity_to_attenuate_color() {
+    use shapes::Sphere;
+
+    let light = Light::Point(PointLight::new(
+        Tuple::point(0.0, 0.0, -10.0),
+        Tuple::color(1.0, 1.0, 1.0),
+    ));
+    let mut shape = Sphere::new();
+    shape.material.ambient = 0.1;
+    shape.material.diffuse = 0.9;
+    shape.material.specular = 0.0;
+    shape.material.color = Tuple::color(1.0, 1.0, 1.0);
+    let point = Tuple::point(0.0, 0.0, -1.0);
+    let eyev = Tuple::vector(0.0, 0.0, -1.0);
+    let normalv = Tuple::vector(0.0, 0.0, -1.0);
+    let examples = [
+        (1.0, Tuple::color(1.0, 1.0, 1.0)),
+        (0.5, Tuple::color(0.55, 0.55, 0.55)),
+        (0.0, Tuple::color(0.1, 0.1, 0.1)),
+    ];
+    for (intensity, result) in examples.iter() {
+        let c = lighting(
+            shape.material,
+            &shape,
+            light,
+            point,
+            eyev,
+            normalv,
+            Tuple::color(*intensity, *intensity, *intensity),
+        );
+        assert_eq!(c, *result);
+    }
+}
+
+#[test]
+fn test_lighting_samples_the_area_light() {
+    use shapes::Sphere;
+
+    let corner = Tuple::point(-0.5, -0.5, -5.0);
+    let v1 = Tuple::vector(1.0, 0.0, 0.0);
+    let v2 = Tuple::vector(0.0, 1.0, 0.0);
+    let light =
+        AreaLight::new(corner, v1, 2, v2, 2, Tuple::color(1.0, 1.0, 1.0));
+    let mut shape = Sphere::new();
+    shape.material.ambient = 0.1;
+    shape.material.diffuse = 0.9;
+    shape.material.specular = 0.0;
+    shape.material.color = Tuple::color(1.0, 1.0, 1.0);
+    let eye = Tuple::point(0.0, 0.0, -5.0);
+    let examples = [
+        (
+            Tuple::point(0.0, 0.0, -1.0),
+            Tuple::color(0.9965, 0.9965, 0.9965),
+        ),
+        (
+            Tuple::point(0.0, 2f32.sqrt() / 2.0, -(2f32.sqrt()) / 2.0),
+            Tuple::color(0.62318, 0.62318, 0.62318),
+        ),
+    ];
+    for (point, result) in examples.iter() {
+        let eyev = (eye - *point).normalize();
+        let normalv = Tuple::vector(point.x, point.y, point.z);
+        let c = lighting(
+            shape.material,
+            &shape,
+            light.into(),
+            *point,
+            eyev,
+            normalv,
+            Tuple::color(1.0, 1.0, 1.0),
+        );
+        assert!((c.red() - result.red()).abs() < 0.0001, "{:?}", c);
+    }
+}
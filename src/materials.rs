@@ -11,6 +11,8 @@ pub struct Material {
     pub shininess: f32,
     pub pattern: Option<Pattern>,
     pub reflective: f32,
+    pub transparency: f32,
+    pub refractive_index: f32,
 }
 
 impl Material {
@@ -30,6 +32,8 @@ impl Material {
             shininess,
             pattern: None,
             reflective,
+            transparency: 0.0,
+            refractive_index: 1.0,
         }
     }
 }
@@ -45,6 +49,8 @@ impl PartialEq for Material {
             && float_eq(self.shininess, other.shininess)
             && self.pattern == other.pattern
             && float_eq(self.reflective, other.reflective)
+            && float_eq(self.transparency, other.transparency)
+            && float_eq(self.refractive_index, other.refractive_index)
     }
 }
 
@@ -58,6 +64,8 @@ impl Default for Material {
             shininess: 200.0,
             pattern: None,
             reflective: 0.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
         }
     }
 }
@@ -72,4 +80,6 @@ fn test_the_default_material() {
     assert_eq!(m.shininess, 200.0);
     assert_eq!(m.pattern, None);
     assert_eq!(m.reflective, 0.0);
+    assert_eq!(m.transparency, 0.0);
+    assert_eq!(m.refractive_index, 1.0);
 }
@@ -0,0 +1,393 @@
+use shapes::{Group, Shape, SmoothTriangle, Triangle};
+use std::fs::File;
+use std::io::prelude::*;
+use std::str::FromStr;
+use tuples::Tuple;
+
+pub struct ObjGroup {
+    pub name: String,
+    pub triangles: Vec<Shape>,
+}
+
+impl ObjGroup {
+    fn new(name: &str) -> Self {
+        ObjGroup {
+            name: name.to_string(),
+            triangles: vec![],
+        }
+    }
+}
+
+pub struct ObjFile {
+    pub vertices: Vec<Tuple>,
+    pub normals: Vec<Tuple>,
+    pub texture_coords: Vec<(f32, f32)>,
+    pub groups: Vec<ObjGroup>,
+    pub ignored: usize,
+}
+
+impl ObjFile {
+    pub fn parse(input: &str) -> Self {
+        let mut obj = ObjFile {
+            vertices: vec![],
+            normals: vec![],
+            texture_coords: vec![],
+            groups: vec![ObjGroup::new("default")],
+            ignored: 0,
+        };
+        for line in input.lines() {
+            if !obj.parse_line(line) {
+                obj.ignored += 1;
+            }
+        }
+        obj
+    }
+
+    pub fn from_file(filename: &str) -> std::io::Result<Self> {
+        let mut file = File::open(filename)?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        Ok(ObjFile::parse(&contents))
+    }
+
+    pub fn group(&self, name: &str) -> Option<&ObjGroup> {
+        self.groups.iter().find(|group| group.name == name)
+    }
+
+    /// Every triangle in the file, ready to be added to a `World`.
+    pub fn shapes(&self) -> Vec<Shape> {
+        self.groups
+            .iter()
+            .flat_map(|group| group.triangles.iter().cloned())
+            .collect()
+    }
+
+    /// A group holding one child group per non-empty group in the file, so
+    /// the whole mesh can be transformed as a unit.
+    pub fn to_group(&self) -> Shape {
+        let mut mesh = Group::new();
+        for obj_group in self.groups.iter() {
+            if obj_group.triangles.is_empty() {
+                continue;
+            }
+            let mut group = Group::new();
+            for triangle in obj_group.triangles.iter() {
+                group.add_child(triangle.clone());
+            }
+            mesh.add_child(group);
+        }
+        mesh
+    }
+
+    fn parse_line(&mut self, line: &str) -> bool {
+        let mut words = line.split_whitespace();
+        let command = match words.next() {
+            Some(command) => command,
+            None => return true,
+        };
+        let args = words.collect::<Vec<_>>();
+        match command {
+            "v" => match parse_floats(&args, 3) {
+                Some(v) => {
+                    self.vertices.push(Tuple::point(v[0], v[1], v[2]));
+                    true
+                }
+                None => false,
+            },
+            "vn" => match parse_floats(&args, 3) {
+                Some(n) => {
+                    self.normals.push(Tuple::vector(n[0], n[1], n[2]));
+                    true
+                }
+                None => false,
+            },
+            "vt" => match parse_floats(&args, 2) {
+                Some(uv) => {
+                    self.texture_coords.push((uv[0], uv[1]));
+                    true
+                }
+                None => false,
+            },
+            "f" => self.parse_face(&args),
+            _ if command.starts_with('#') => true,
+            "g" | "o" => {
+                let name = if args.is_empty() {
+                    "default".to_string()
+                } else {
+                    args.join(" ")
+                };
+                if self.group(&name).is_none() {
+                    self.groups.push(ObjGroup::new(&name));
+                }
+                let idx = self
+                    .groups
+                    .iter()
+                    .position(|group| group.name == name)
+                    .unwrap();
+                // Keep the current group last so faces are added to it
+                let group = self.groups.remove(idx);
+                self.groups.push(group);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn parse_face(&mut self, args: &[&str]) -> bool {
+        if args.len() < 3 {
+            return false;
+        }
+        let mut corners = Vec::with_capacity(args.len());
+        for arg in args {
+            match self.parse_face_vertex(arg) {
+                Some(corner) => corners.push(corner),
+                None => return false,
+            }
+        }
+        let triangles = fan_triangulation(&corners);
+        self.groups.last_mut().unwrap().triangles.extend(triangles);
+        true
+    }
+
+    /// Parses a `v`, `v/vt`, `v//vn` or `v/vt/vn` face vertex.
+    fn parse_face_vertex(&self, arg: &str) -> Option<(Tuple, Option<Tuple>)> {
+        let mut parts = arg.split('/');
+        let vertex =
+            parts.next().and_then(|idx| lookup(&self.vertices, idx))?;
+        let _texture = parts.next();
+        let normal = match parts.next() {
+            Some(idx) if !idx.is_empty() => Some(lookup(&self.normals, idx)?),
+            _ => None,
+        };
+        Some((vertex, normal))
+    }
+}
+
+fn parse_floats(args: &[&str], count: usize) -> Option<Vec<f32>> {
+    if args.len() < count {
+        return None;
+    }
+    args.iter()
+        .take(count)
+        .map(|arg| f32::from_str(arg).ok())
+        .collect()
+}
+
+/// OBJ indices start at 1, and negative indices count back from the most
+/// recently defined element.
+fn lookup(items: &[Tuple], idx: &str) -> Option<Tuple> {
+    let idx = i64::from_str(idx).ok()?;
+    let position = if idx > 0 {
+        idx - 1
+    } else {
+        items.len() as i64 + idx
+    };
+    if position < 0 {
+        None
+    } else {
+        items.get(position as usize).cloned()
+    }
+}
+
+fn fan_triangulation(corners: &[(Tuple, Option<Tuple>)]) -> Vec<Shape> {
+    let (p1, n1) = corners[0];
+    corners[1..]
+        .windows(2)
+        .map(|pair| {
+            let (p2, n2) = pair[0];
+            let (p3, n3) = pair[1];
+            match (n1, n2, n3) {
+                (Some(n1), Some(n2), Some(n3)) => {
+                    SmoothTriangle::new(p1, p2, p3, n1, n2, n3)
+                }
+                _ => Triangle::new(p1, p2, p3),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+use shapes::ShapeKind;
+
+#[cfg(test)]
+fn triangle_points(shape: &Shape) -> (Tuple, Tuple, Tuple) {
+    match shape.shape_kind {
+        ShapeKind::Triangle { p1, p2, p3, .. }
+        | ShapeKind::SmoothTriangle { p1, p2, p3, .. } => (p1, p2, p3),
+        _ => panic!("expected a triangle"),
+    }
+}
+
+#[test]
+fn test_ignoring_unrecognized_lines() {
+    let gibberish = "There was a young lady named Bright
+who traveled much faster than light.
+She set out one day
+in a relative way,
+and came back the previous night.";
+    let obj = ObjFile::parse(gibberish);
+    assert_eq!(obj.ignored, 5);
+    assert!(obj.shapes().is_empty());
+}
+
+#[test]
+fn test_vertex_records() {
+    let file = "v -1 1 0
+v -1.0000 0.5000 0.0000
+v 1 0 0
+v 1 1 0";
+    let obj = ObjFile::parse(file);
+    assert_eq!(obj.ignored, 0);
+    assert_eq!(obj.vertices[0], Tuple::point(-1.0, 1.0, 0.0));
+    assert_eq!(obj.vertices[1], Tuple::point(-1.0, 0.5, 0.0));
+    assert_eq!(obj.vertices[2], Tuple::point(1.0, 0.0, 0.0));
+    assert_eq!(obj.vertices[3], Tuple::point(1.0, 1.0, 0.0));
+}
+
+#[test]
+fn test_malformed_records_are_ignored() {
+    let file = "v 1 2
+v 1 two 3
+f 1 2 3
+vn 0 1";
+    let obj = ObjFile::parse(file);
+    assert_eq!(obj.ignored, 4);
+    assert!(obj.vertices.is_empty());
+    assert!(obj.normals.is_empty());
+}
+
+#[test]
+fn test_parsing_triangle_faces() {
+    let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+# two triangles
+f 1 2 3
+f 1 3 4";
+    let obj = ObjFile::parse(file);
+    assert_eq!(obj.ignored, 0);
+    let shapes = obj.group("default").unwrap().triangles.clone();
+    assert_eq!(shapes.len(), 2);
+    assert_eq!(
+        triangle_points(&shapes[0]),
+        (obj.vertices[0], obj.vertices[1], obj.vertices[2])
+    );
+    assert_eq!(
+        triangle_points(&shapes[1]),
+        (obj.vertices[0], obj.vertices[2], obj.vertices[3])
+    );
+}
+
+#[test]
+fn test_triangulating_polygons() {
+    let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+v 0 2 0
+
+f 1 2 3 4 5";
+    let obj = ObjFile::parse(file);
+    let shapes = obj.shapes();
+    assert_eq!(shapes.len(), 3);
+    let v = &obj.vertices;
+    assert_eq!(triangle_points(&shapes[0]), (v[0], v[1], v[2]));
+    assert_eq!(triangle_points(&shapes[1]), (v[0], v[2], v[3]));
+    assert_eq!(triangle_points(&shapes[2]), (v[0], v[3], v[4]));
+}
+
+#[test]
+fn test_triangles_in_groups() {
+    let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+g FirstGroup
+f 1 2 3
+o SecondGroup
+f 1 3 4";
+    let obj = ObjFile::parse(file);
+    let first = obj.group("FirstGroup").unwrap();
+    let second = obj.group("SecondGroup").unwrap();
+    let v = &obj.vertices;
+    assert_eq!(first.triangles.len(), 1);
+    assert_eq!(triangle_points(&first.triangles[0]), (v[0], v[1], v[2]));
+    assert_eq!(second.triangles.len(), 1);
+    assert_eq!(triangle_points(&second.triangles[0]), (v[0], v[2], v[3]));
+    assert_eq!(obj.shapes().len(), 2);
+}
+
+#[test]
+fn test_vertex_normal_and_texture_records() {
+    let file = "vn 0 0 1
+vn 0.707 0 -0.707
+vn 1 2 3
+vt 0.5 0.25";
+    let obj = ObjFile::parse(file);
+    assert_eq!(obj.normals[0], Tuple::vector(0.0, 0.0, 1.0));
+    assert_eq!(obj.normals[1], Tuple::vector(0.707, 0.0, -0.707));
+    assert_eq!(obj.normals[2], Tuple::vector(1.0, 2.0, 3.0));
+    assert_eq!(obj.texture_coords, vec![(0.5, 0.25)]);
+}
+
+#[test]
+fn test_faces_with_normals() {
+    let file = "v 0 1 0
+v -1 0 0
+v 1 0 0
+
+vn -1 0 0
+vn 1 0 0
+vn 0 1 0
+
+f 1//3 2//1 3//2
+f 1/0/3 2/102/1 3/14/2
+f -3//-1 -2//-3 -1//-2";
+    let obj = ObjFile::parse(file);
+    let shapes = obj.shapes();
+    assert_eq!(shapes.len(), 3);
+    for shape in shapes.iter() {
+        match shape.shape_kind {
+            ShapeKind::SmoothTriangle {
+                p1,
+                p2,
+                p3,
+                n1,
+                n2,
+                n3,
+                ..
+            } => {
+                assert_eq!(p1, obj.vertices[0]);
+                assert_eq!(p2, obj.vertices[1]);
+                assert_eq!(p3, obj.vertices[2]);
+                assert_eq!(n1, obj.normals[2]);
+                assert_eq!(n2, obj.normals[0]);
+                assert_eq!(n3, obj.normals[1]);
+            }
+            _ => panic!("expected a smooth triangle"),
+        }
+    }
+}
+
+#[test]
+fn test_converting_an_obj_file_to_a_group() {
+    let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+g FirstGroup
+f 1 2 3
+g SecondGroup
+f 1 3 4";
+    let obj = ObjFile::parse(file);
+    let mesh = obj.to_group();
+    let mut first = Group::new();
+    first.add_child(obj.group("FirstGroup").unwrap().triangles[0].clone());
+    let mut second = Group::new();
+    second.add_child(obj.group("SecondGroup").unwrap().triangles[0].clone());
+    assert_eq!(mesh.children(), &[first, second]);
+}
@@ -89,10 +89,10 @@ impl Pattern {
 
 pub fn pattern_at_shape(
     pattern: Pattern,
-    object: Shape,
+    object: &Shape,
     point: Tuple,
 ) -> Tuple {
-    let object_space = object.transform.inverse() * point;
+    let object_space = object.world_to_object(point);
     let pattern_space = pattern.transform.inverse() * object_space;
     pattern.color_at(pattern_space)
 }
@@ -164,7 +164,7 @@ mod tests {
         shape.transform = Matrix4::scaling(2.0, 2.0, 2.0);
         let pattern = test_pattern();
         assert_eq!(
-            pattern_at_shape(pattern, shape, Tuple::point(2.0, 3.0, 4.0)),
+            pattern_at_shape(pattern, &shape, Tuple::point(2.0, 3.0, 4.0)),
             Tuple::color(1.0, 1.5, 2.0)
         );
     }
@@ -175,7 +175,7 @@ mod tests {
         let mut pattern = test_pattern();
         pattern.transform = Matrix4::scaling(2.0, 2.0, 2.0);
         assert_eq!(
-            pattern_at_shape(pattern, shape, Tuple::point(2.0, 3.0, 4.0)),
+            pattern_at_shape(pattern, &shape, Tuple::point(2.0, 3.0, 4.0)),
             Tuple::color(1.0, 1.5, 2.0)
         );
     }
@@ -187,7 +187,7 @@ mod tests {
         let mut pattern = test_pattern();
         pattern.transform = Matrix4::translation(0.5, 1.0, 1.5);
         assert_eq!(
-            pattern_at_shape(pattern, shape, Tuple::point(2.5, 3.0, 3.5)),
+            pattern_at_shape(pattern, &shape, Tuple::point(2.5, 3.0, 3.5)),
             Tuple::color(0.75, 0.5, 0.25)
         );
     }
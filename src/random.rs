@@ -0,0 +1,108 @@
+use std::cell::Cell;
+use std::f32::consts::PI;
+use tuples::Tuple;
+
+thread_local! {
+    static STATE: Cell<u32> = const { Cell::new(0x2545_f491) };
+}
+
+/// Restarts this thread's sequence, so random renders can be reproduced.
+pub fn seed(seed: u32) {
+    // xorshift gets stuck at zero
+    STATE.with(|state| state.set(if seed == 0 { 1 } else { seed }));
+}
+
+/// A pseudo-random number in `[0, 1)`, good enough for jittering samples.
+pub fn random() -> f32 {
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        state.set(x);
+        (x >> 8) as f32 / (1u32 << 24) as f32
+    })
+}
+
+#[test]
+fn test_random_numbers_are_between_zero_and_one() {
+    for _ in 0..1000 {
+        let n = random();
+        assert!((0.0..1.0).contains(&n));
+    }
+}
+
+#[test]
+fn test_seeding_repeats_the_sequence() {
+    seed(42);
+    let first = (0..10).map(|_| random()).collect::<Vec<_>>();
+    seed(42);
+    let second = (0..10).map(|_| random()).collect::<Vec<_>>();
+    assert_eq!(first, second);
+    assert!(first.iter().any(|n| *n != first[0]));
+}
+
+/// A random point in a disk of radius 1 around the origin.
+pub fn point_in_disk() -> (f32, f32) {
+    let radius = random().sqrt();
+    let theta = 2.0 * PI * random();
+    (radius * theta.cos(), radius * theta.sin())
+}
+
+/// A cosine-weighted direction in the hemisphere around `normal`.
+pub fn cosine_weighted_direction(normal: Tuple) -> Tuple {
+    direction_around(normal, random().sqrt())
+}
+
+/// A direction around `axis`, tighter the higher `exponent` is.
+pub fn phong_lobe_direction(axis: Tuple, exponent: f32) -> Tuple {
+    direction_around(axis, random().powf(1.0 / (exponent + 1.0)))
+}
+
+/// A direction at a random angle around `axis`, `cos_theta` away from it.
+fn direction_around(axis: Tuple, cos_theta: f32) -> Tuple {
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = 2.0 * PI * random();
+    let helper = if axis.x.abs() > 0.9 {
+        Tuple::vector(0.0, 1.0, 0.0)
+    } else {
+        Tuple::vector(1.0, 0.0, 0.0)
+    };
+    let tangent = helper.cross(axis).normalize();
+    let bitangent = axis.cross(tangent);
+    (tangent * (sin_theta * phi.cos())
+        + bitangent * (sin_theta * phi.sin())
+        + axis * cos_theta)
+        .normalize()
+}
+
+#[test]
+fn test_points_in_a_disk() {
+    for _ in 0..1000 {
+        let (x, y) = point_in_disk();
+        assert!(x * x + y * y <= 1.0);
+    }
+}
+
+#[test]
+fn test_cosine_weighted_directions_are_in_the_hemisphere() {
+    let normal = Tuple::vector(0.0, 0.0, 1.0);
+    for _ in 0..1000 {
+        let direction = cosine_weighted_direction(normal);
+        assert!(direction.dot(normal) >= 0.0);
+        assert!((direction.magnitude() - 1.0).abs() < 0.0001);
+    }
+}
+
+#[test]
+fn test_phong_lobe_directions_tighten_with_the_exponent() {
+    let axis = Tuple::vector(1.0, 0.0, 0.0);
+    let mean_cos = |exponent| {
+        (0..1000)
+            .map(|_| phong_lobe_direction(axis, exponent).dot(axis))
+            .sum::<f32>()
+            / 1000.0
+    };
+    let (wide, narrow) = (mean_cos(1.0), mean_cos(200.0));
+    assert!(wide > 0.0 && narrow > wide && narrow > 0.99);
+}
@@ -1,4 +1,5 @@
-use super::float_eq;
+use super::{float_eq, EPSILON};
+use bounds::{check_axis, BoundingBox};
 use intersections::Intersection;
 use materials::Material;
 use matrices::Matrix4;
@@ -6,22 +7,95 @@ use rays::Ray;
 use tuples::Tuple;
 
 #[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+#[derive(Clone, PartialEq, Debug)]
 pub enum ShapeKind {
     Sphere,
     Plane,
+    Cube,
+    Cylinder {
+        minimum: f32,
+        maximum: f32,
+        closed: bool,
+    },
+    Cone {
+        minimum: f32,
+        maximum: f32,
+        closed: bool,
+    },
+    Triangle {
+        p1: Tuple,
+        p2: Tuple,
+        p3: Tuple,
+        e1: Tuple,
+        e2: Tuple,
+        normal: Tuple,
+    },
+    SmoothTriangle {
+        p1: Tuple,
+        p2: Tuple,
+        p3: Tuple,
+        n1: Tuple,
+        n2: Tuple,
+        n3: Tuple,
+        e1: Tuple,
+        e2: Tuple,
+    },
+    Group {
+        children: Vec<Shape>,
+        bounds: BoundingBox,
+    },
+    Csg {
+        operation: CsgOperation,
+        left: Box<Shape>,
+        right: Box<Shape>,
+        bounds: BoundingBox,
+    },
 }
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct Shape {
     pub transform: Matrix4,
     pub material: Material,
     pub shape_kind: ShapeKind,
+    /// Whether the shape blocks light from reaching other objects.
+    pub casts_shadow: bool,
+    /// Whether other objects can block light from reaching the shape.
+    pub receives_shadow: bool,
 }
 
 impl Shape {
     pub fn normal_at(&self, point: Tuple) -> Tuple {
-        let local_point = self.transform.inverse() * point;
+        let local_point = self.world_to_object(point);
         let local_normal = self.local_normal_at(local_point);
+        self.normal_to_world(local_normal)
+    }
+
+    /// Like `normal_at`, but smooth triangles interpolate their vertex
+    /// normals using the `u`/`v` recorded on the hit.
+    pub fn normal_at_hit(&self, point: Tuple, hit: &Intersection) -> Tuple {
+        let local_normal = match self.shape_kind {
+            ShapeKind::SmoothTriangle { n1, n2, n3, .. } => {
+                n2 * hit.u + n3 * hit.v + n1 * (1.0 - hit.u - hit.v)
+            }
+            _ => self.local_normal_at(self.world_to_object(point)),
+        };
+        self.normal_to_world(local_normal)
+    }
+
+    /// Shapes are only ever hit through their parent groups, which fold
+    /// their own transforms into the child they hand back, so a hit's
+    /// object transform already covers the whole parent chain.
+    pub fn world_to_object(&self, point: Tuple) -> Tuple {
+        self.transform.inverse() * point
+    }
+
+    pub fn normal_to_world(&self, local_normal: Tuple) -> Tuple {
         let mut world_normal =
             self.transform.inverse().transpose() * local_normal;
         world_normal.w = 0.0;
@@ -32,6 +106,148 @@ impl Shape {
         match self.shape_kind {
             ShapeKind::Plane => Tuple::vector(0.0, 1.0, 0.0),
             ShapeKind::Sphere => point - Tuple::point(0.0, 0.0, 0.0),
+            ShapeKind::Cube => {
+                let maxc = point.x.abs().max(point.y.abs()).max(point.z.abs());
+                if maxc == point.x.abs() {
+                    Tuple::vector(point.x, 0.0, 0.0)
+                } else if maxc == point.y.abs() {
+                    Tuple::vector(0.0, point.y, 0.0)
+                } else {
+                    Tuple::vector(0.0, 0.0, point.z)
+                }
+            }
+            ShapeKind::Cylinder {
+                minimum, maximum, ..
+            } => {
+                let dist = point.x.powi(2) + point.z.powi(2);
+                if dist < 1.0 && point.y >= maximum - EPSILON {
+                    Tuple::vector(0.0, 1.0, 0.0)
+                } else if dist < 1.0 && point.y <= minimum + EPSILON {
+                    Tuple::vector(0.0, -1.0, 0.0)
+                } else {
+                    Tuple::vector(point.x, 0.0, point.z)
+                }
+            }
+            ShapeKind::Cone {
+                minimum, maximum, ..
+            } => {
+                let dist = point.x.powi(2) + point.z.powi(2);
+                if dist < maximum.powi(2) && point.y >= maximum - EPSILON {
+                    Tuple::vector(0.0, 1.0, 0.0)
+                } else if dist < minimum.powi(2) && point.y <= minimum + EPSILON
+                {
+                    Tuple::vector(0.0, -1.0, 0.0)
+                } else {
+                    let mut y = dist.sqrt();
+                    if point.y > 0.0 {
+                        y = -y;
+                    }
+                    Tuple::vector(point.x, y, point.z)
+                }
+            }
+            ShapeKind::Triangle { normal, .. } => normal,
+            ShapeKind::SmoothTriangle { e1, e2, .. } => {
+                e2.cross(e1).normalize()
+            }
+            ShapeKind::Group { .. } | ShapeKind::Csg { .. } => {
+                panic!("groups and CSG shapes have no normal, only their children do")
+            }
+        }
+    }
+
+    pub fn add_child(&mut self, child: Shape) {
+        match self.shape_kind {
+            ShapeKind::Group {
+                ref mut children,
+                ref mut bounds,
+            } => {
+                bounds.merge(&child.parent_space_bounds());
+                children.push(child);
+            }
+            _ => panic!("only groups can have children"),
+        }
+    }
+
+    pub fn children(&self) -> &[Shape] {
+        match self.shape_kind {
+            ShapeKind::Group { ref children, .. } => children,
+            _ => &[],
+        }
+    }
+
+    /// The untransformed bounds of the shape, in object space.
+    pub fn bounds(&self) -> BoundingBox {
+        match self.shape_kind {
+            ShapeKind::Sphere | ShapeKind::Cube => BoundingBox::new(
+                Tuple::point(-1.0, -1.0, -1.0),
+                Tuple::point(1.0, 1.0, 1.0),
+            ),
+            ShapeKind::Plane => BoundingBox::new(
+                Tuple::point(-f32::INFINITY, 0.0, -f32::INFINITY),
+                Tuple::point(f32::INFINITY, 0.0, f32::INFINITY),
+            ),
+            ShapeKind::Cylinder {
+                minimum, maximum, ..
+            } => BoundingBox::new(
+                Tuple::point(-1.0, minimum, -1.0),
+                Tuple::point(1.0, maximum, 1.0),
+            ),
+            ShapeKind::Cone {
+                minimum, maximum, ..
+            } => {
+                let limit = minimum.abs().max(maximum.abs());
+                BoundingBox::new(
+                    Tuple::point(-limit, minimum, -limit),
+                    Tuple::point(limit, maximum, limit),
+                )
+            }
+            ShapeKind::Triangle { p1, p2, p3, .. }
+            | ShapeKind::SmoothTriangle { p1, p2, p3, .. } => {
+                let mut bounds = BoundingBox::default();
+                bounds.add_point(p1);
+                bounds.add_point(p2);
+                bounds.add_point(p3);
+                bounds
+            }
+            ShapeKind::Group { bounds, .. } | ShapeKind::Csg { bounds, .. } => {
+                bounds
+            }
+        }
+    }
+
+    /// The bounds of the shape once its transform has been applied.
+    pub fn parent_space_bounds(&self) -> BoundingBox {
+        self.bounds().transform(self.transform)
+    }
+
+    /// Builds a bounding volume hierarchy by splitting any group with at
+    /// least `threshold` children into two subgroups, recursively.
+    pub fn divide(&mut self, threshold: usize) {
+        match self.shape_kind {
+            ShapeKind::Group {
+                ref mut children, ..
+            } => {
+                if children.len() >= threshold {
+                    if let Some((left, right)) = partition_children(children) {
+                        children.push(subgroup(left));
+                        children.push(subgroup(right));
+                    }
+                }
+                for child in children.iter_mut() {
+                    child.divide(threshold);
+                }
+            }
+            ShapeKind::Csg {
+                ref mut left,
+                ref mut right,
+                ref mut bounds,
+                ..
+            } => {
+                left.divide(threshold);
+                right.divide(threshold);
+                *bounds = csg_bounds(left, right);
+            }
+            _ => {}
         }
     }
 
@@ -76,8 +292,301 @@ impl Shape {
                     }
                 }
             }
+            ShapeKind::Cube => {
+                let (xtmin, xtmax) =
+                    check_axis(ray.origin.x, ray.direction.x, -1.0, 1.0);
+                let (ytmin, ytmax) =
+                    check_axis(ray.origin.y, ray.direction.y, -1.0, 1.0);
+                let (ztmin, ztmax) =
+                    check_axis(ray.origin.z, ray.direction.z, -1.0, 1.0);
+                let tmin = xtmin.max(ytmin).max(ztmin);
+                let tmax = xtmax.min(ytmax).min(ztmax);
+                if tmin > tmax {
+                    vec![]
+                } else {
+                    vec![
+                        Intersection::new(tmin, self.clone()),
+                        Intersection::new(tmax, self.clone()),
+                    ]
+                }
+            }
+            ShapeKind::Cylinder {
+                minimum,
+                maximum,
+                closed,
+            } => {
+                let mut xs = vec![];
+                let a = ray.direction.x.powi(2) + ray.direction.z.powi(2);
+                if !float_eq(a, 0.0) {
+                    let b = 2.0 * ray.origin.x * ray.direction.x
+                        + 2.0 * ray.origin.z * ray.direction.z;
+                    let c = ray.origin.x.powi(2) + ray.origin.z.powi(2) - 1.0;
+                    let disc = b.powi(2) - 4.0 * a * c;
+                    if disc < 0.0 {
+                        return vec![];
+                    }
+                    let mut t0 = (-b - disc.sqrt()) / (2.0 * a);
+                    let mut t1 = (-b + disc.sqrt()) / (2.0 * a);
+                    if t0 > t1 {
+                        std::mem::swap(&mut t0, &mut t1);
+                    }
+                    for t in [t0, t1].iter() {
+                        let y = ray.origin.y + t * ray.direction.y;
+                        if minimum < y && y < maximum {
+                            xs.push(Intersection::new(*t, self.clone()));
+                        }
+                    }
+                }
+                if closed {
+                    self.intersect_caps(
+                        &ray,
+                        (minimum, 1.0),
+                        (maximum, 1.0),
+                        &mut xs,
+                    );
+                }
+                xs
+            }
+            ShapeKind::Cone {
+                minimum,
+                maximum,
+                closed,
+            } => {
+                let mut xs = vec![];
+                let a = ray.direction.x.powi(2) - ray.direction.y.powi(2)
+                    + ray.direction.z.powi(2);
+                let b = 2.0 * ray.origin.x * ray.direction.x
+                    - 2.0 * ray.origin.y * ray.direction.y
+                    + 2.0 * ray.origin.z * ray.direction.z;
+                let c = ray.origin.x.powi(2) - ray.origin.y.powi(2)
+                    + ray.origin.z.powi(2);
+                if float_eq(a, 0.0) {
+                    if !float_eq(b, 0.0) {
+                        let t = -c / (2.0 * b);
+                        xs.push(Intersection::new(t, self.clone()));
+                    }
+                } else {
+                    // Rays grazing the surface can give a discriminant
+                    // that is only negative because of f32 rounding.
+                    let disc = b.powi(2) - 4.0 * a * c;
+                    if disc < -EPSILON {
+                        return vec![];
+                    }
+                    let disc = disc.max(0.0);
+                    let mut t0 = (-b - disc.sqrt()) / (2.0 * a);
+                    let mut t1 = (-b + disc.sqrt()) / (2.0 * a);
+                    if t0 > t1 {
+                        std::mem::swap(&mut t0, &mut t1);
+                    }
+                    for t in [t0, t1].iter() {
+                        let y = ray.origin.y + t * ray.direction.y;
+                        if minimum < y && y < maximum {
+                            xs.push(Intersection::new(*t, self.clone()));
+                        }
+                    }
+                }
+                if closed {
+                    self.intersect_caps(
+                        &ray,
+                        (minimum, minimum.abs()),
+                        (maximum, maximum.abs()),
+                        &mut xs,
+                    );
+                }
+                xs
+            }
+            ShapeKind::Triangle { p1, e1, e2, .. }
+            | ShapeKind::SmoothTriangle { p1, e1, e2, .. } => {
+                let dir_cross_e2 = ray.direction.cross(e2);
+                let det = e1.dot(dir_cross_e2);
+                if det.abs() < EPSILON {
+                    return vec![];
+                }
+                let f = 1.0 / det;
+                let p1_to_origin = ray.origin - p1;
+                let u = f * p1_to_origin.dot(dir_cross_e2);
+                if !(0.0..=1.0).contains(&u) {
+                    return vec![];
+                }
+                let origin_cross_e1 = p1_to_origin.cross(e1);
+                let v = f * ray.direction.dot(origin_cross_e1);
+                if v < 0.0 || (u + v) > 1.0 {
+                    return vec![];
+                }
+                let t = f * e2.dot(origin_cross_e1);
+                vec![Intersection::new_with_uv(t, self.clone(), u, v)]
+            }
+            ShapeKind::Group {
+                ref children,
+                ref bounds,
+            } => {
+                if !bounds.intersects(&ray) {
+                    return vec![];
+                }
+                let mut xs = children
+                    .iter()
+                    .flat_map(|child| self.child_intersect(child, &ray))
+                    .collect::<Vec<Intersection>>();
+                xs.sort_unstable();
+                xs
+            }
+            ShapeKind::Csg {
+                operation,
+                ref left,
+                ref right,
+                ..
+            } => {
+                if !self.bounds().intersects(&ray) {
+                    return vec![];
+                }
+                let mut xs = self
+                    .child_intersect(left, &ray)
+                    .into_iter()
+                    .map(|hit| (true, hit))
+                    .chain(
+                        self.child_intersect(right, &ray)
+                            .into_iter()
+                            .map(|hit| (false, hit)),
+                    )
+                    .collect::<Vec<(bool, Intersection)>>();
+                xs.sort_unstable_by(|a, b| a.1.cmp(&b.1));
+                filter_intersections(operation, xs)
+            }
+        }
+    }
+
+    /// Intersects a child of a group or CSG shape, folding this shape's
+    /// transform into the object on each hit.
+    fn child_intersect(&self, child: &Shape, ray: &Ray) -> Vec<Intersection> {
+        child
+            .intersect(ray)
+            .into_iter()
+            .map(|mut hit| {
+                hit.object.transform = self.transform * hit.object.transform;
+                hit.object.casts_shadow &= self.casts_shadow;
+                hit.object.receives_shadow &= self.receives_shadow;
+                hit
+            })
+            .collect()
+    }
+
+    /// Caps are given as `(y, radius)` pairs for the bottom and top ends.
+    fn intersect_caps(
+        &self,
+        ray: &Ray,
+        lower: (f32, f32),
+        upper: (f32, f32),
+        xs: &mut Vec<Intersection>,
+    ) {
+        if float_eq(ray.direction.y, 0.0) {
+            return;
+        }
+        for (cap, radius) in [lower, upper].iter() {
+            let t = (cap - ray.origin.y) / ray.direction.y;
+            if check_cap(ray, t, *radius) {
+                xs.push(Intersection::new(t, self.clone()));
+            }
+        }
+    }
+}
+
+fn check_cap(ray: &Ray, t: f32, radius: f32) -> bool {
+    let x = ray.origin.x + t * ray.direction.x;
+    let z = ray.origin.z + t * ray.direction.z;
+    (x.powi(2) + z.powi(2)) <= radius.powi(2) + EPSILON
+}
+
+/// Splits off children on either side of the midpoint of their centres,
+/// along the axis where the centres are most spread out. Children with
+/// infinite bounds, like planes, can't be placed and stay where they are.
+fn partition_children(
+    children: &mut Vec<Shape>,
+) -> Option<(Vec<Shape>, Vec<Shape>)> {
+    let mut centres = BoundingBox::default();
+    for child in children.iter() {
+        let bounds = child.parent_space_bounds();
+        if bounds.is_finite() {
+            centres.add_point(bounds.centre());
+        }
+    }
+    if centres.is_empty() {
+        return None;
+    }
+    let extent = centres.max - centres.min;
+    let axis = |point: Tuple| {
+        if extent.x >= extent.y && extent.x >= extent.z {
+            point.x
+        } else if extent.y >= extent.z {
+            point.y
+        } else {
+            point.z
+        }
+    };
+    if axis(extent) <= 0.0 {
+        return None;
+    }
+    let midpoint = axis(centres.centre());
+    let mut left = vec![];
+    let mut right = vec![];
+    let mut rest = vec![];
+    for child in children.drain(..) {
+        let bounds = child.parent_space_bounds();
+        if !bounds.is_finite() {
+            rest.push(child);
+        } else if axis(bounds.centre()) < midpoint {
+            left.push(child);
+        } else {
+            right.push(child);
+        }
+    }
+    *children = rest;
+    Some((left, right))
+}
+
+fn subgroup(mut children: Vec<Shape>) -> Shape {
+    if children.len() == 1 {
+        return children.remove(0);
+    }
+    let mut group = Group::new();
+    for child in children {
+        group.add_child(child);
+    }
+    group
+}
+
+fn intersection_allowed(
+    operation: CsgOperation,
+    lhit: bool,
+    inl: bool,
+    inr: bool,
+) -> bool {
+    match operation {
+        CsgOperation::Union => (lhit && !inr) || (!lhit && !inl),
+        CsgOperation::Intersection => (lhit && inr) || (!lhit && inl),
+        CsgOperation::Difference => (lhit && !inr) || (!lhit && inl),
+    }
+}
+
+/// Takes intersections sorted by `t` and tagged with whether they hit the
+/// left operand, keeping those on the surface of the combined shape.
+fn filter_intersections(
+    operation: CsgOperation,
+    xs: Vec<(bool, Intersection)>,
+) -> Vec<Intersection> {
+    let mut inl = false;
+    let mut inr = false;
+    let mut result = vec![];
+    for (lhit, hit) in xs {
+        if intersection_allowed(operation, lhit, inl, inr) {
+            result.push(hit);
+        }
+        if lhit {
+            inl = !inl;
+        } else {
+            inr = !inr;
         }
     }
+    result
 }
 
 impl Default for Shape {
@@ -86,6 +595,8 @@ impl Default for Shape {
             transform: Matrix4::default(),
             material: Material::default(),
             shape_kind: ShapeKind::Sphere,
+            casts_shadow: true,
+            receives_shadow: true,
         }
     }
 }
@@ -126,6 +637,8 @@ impl Sphere {
             transform: Matrix4::default(),
             material: Material::default(),
             shape_kind: ShapeKind::Sphere,
+            casts_shadow: true,
+            receives_shadow: true,
         }
     }
 }
@@ -136,6 +649,13 @@ fn test_a_spheres_default_transformation() {
     assert_eq!(s.transform, Matrix4::default());
 }
 
+#[test]
+fn test_shapes_cast_and_receive_shadows_by_default() {
+    let s = Sphere::new();
+    assert!(s.casts_shadow);
+    assert!(s.receives_shadow);
+}
+
 #[test]
 fn test_changing_a_spheres_transformation() {
     let mut s = Sphere::new();
@@ -362,3 +882,1105 @@ fn test_a_ray_intersecting_a_plane_from_below() {
     assert_eq!(xs[0].t, 1.0);
     assert_eq!(xs[0].object, p);
 }
+
+pub struct Cube {}
+
+impl Cube {
+    pub fn new() -> Shape {
+        let mut shape = Shape::default();
+        shape.shape_kind = ShapeKind::Cube;
+        shape
+    }
+}
+
+#[test]
+fn test_a_ray_intersects_a_cube() {
+    let c = Cube::new();
+    let examples = [
+        (
+            Tuple::point(5.0, 0.5, 0.0),
+            Tuple::vector(-1.0, 0.0, 0.0),
+            4.0,
+            6.0,
+        ),
+        (
+            Tuple::point(-5.0, 0.5, 0.0),
+            Tuple::vector(1.0, 0.0, 0.0),
+            4.0,
+            6.0,
+        ),
+        (
+            Tuple::point(0.5, 5.0, 0.0),
+            Tuple::vector(0.0, -1.0, 0.0),
+            4.0,
+            6.0,
+        ),
+        (
+            Tuple::point(0.5, -5.0, 0.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+            4.0,
+            6.0,
+        ),
+        (
+            Tuple::point(0.5, 0.0, 5.0),
+            Tuple::vector(0.0, 0.0, -1.0),
+            4.0,
+            6.0,
+        ),
+        (
+            Tuple::point(0.5, 0.0, -5.0),
+            Tuple::vector(0.0, 0.0, 1.0),
+            4.0,
+            6.0,
+        ),
+        (
+            Tuple::point(0.0, 0.5, 0.0),
+            Tuple::vector(0.0, 0.0, 1.0),
+            -1.0,
+            1.0,
+        ),
+    ];
+    for (origin, direction, t1, t2) in examples.iter() {
+        let r = Ray::new(*origin, *direction);
+        let xs = c.local_intersect(r);
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, *t1);
+        assert_eq!(xs[1].t, *t2);
+    }
+}
+
+#[test]
+fn test_a_ray_misses_a_cube() {
+    let c = Cube::new();
+    let examples = [
+        (
+            Tuple::point(-2.0, 0.0, 0.0),
+            Tuple::vector(0.2673, 0.5345, 0.8018),
+        ),
+        (
+            Tuple::point(0.0, -2.0, 0.0),
+            Tuple::vector(0.8018, 0.2673, 0.5345),
+        ),
+        (
+            Tuple::point(0.0, 0.0, -2.0),
+            Tuple::vector(0.5345, 0.8018, 0.2673),
+        ),
+        (Tuple::point(2.0, 0.0, 2.0), Tuple::vector(0.0, 0.0, -1.0)),
+        (Tuple::point(0.0, 2.0, 2.0), Tuple::vector(0.0, -1.0, 0.0)),
+        (Tuple::point(2.0, 2.0, 0.0), Tuple::vector(-1.0, 0.0, 0.0)),
+    ];
+    for (origin, direction) in examples.iter() {
+        let r = Ray::new(*origin, *direction);
+        let xs = c.local_intersect(r);
+        assert!(xs.is_empty());
+    }
+}
+
+#[test]
+fn test_the_normal_on_the_surface_of_a_cube() {
+    let c = Cube::new();
+    let examples = [
+        (Tuple::point(1.0, 0.5, -0.8), Tuple::vector(1.0, 0.0, 0.0)),
+        (Tuple::point(-1.0, -0.2, 0.9), Tuple::vector(-1.0, 0.0, 0.0)),
+        (Tuple::point(-0.4, 1.0, -0.1), Tuple::vector(0.0, 1.0, 0.0)),
+        (Tuple::point(0.3, -1.0, -0.7), Tuple::vector(0.0, -1.0, 0.0)),
+        (Tuple::point(-0.6, 0.3, 1.0), Tuple::vector(0.0, 0.0, 1.0)),
+        (Tuple::point(0.4, 0.4, -1.0), Tuple::vector(0.0, 0.0, -1.0)),
+        (Tuple::point(1.0, 1.0, 1.0), Tuple::vector(1.0, 0.0, 0.0)),
+        (
+            Tuple::point(-1.0, -1.0, -1.0),
+            Tuple::vector(-1.0, 0.0, 0.0),
+        ),
+    ];
+    for (point, normal) in examples.iter() {
+        assert_eq!(c.local_normal_at(*point), *normal);
+    }
+}
+
+pub struct Cylinder {}
+
+impl Cylinder {
+    pub fn new() -> Shape {
+        let mut shape = Shape::default();
+        shape.shape_kind = ShapeKind::Cylinder {
+            minimum: -f32::INFINITY,
+            maximum: f32::INFINITY,
+            closed: false,
+        };
+        shape
+    }
+}
+
+#[cfg(test)]
+fn truncated_cylinder(minimum: f32, maximum: f32, closed: bool) -> Shape {
+    let mut shape = Cylinder::new();
+    shape.shape_kind = ShapeKind::Cylinder {
+        minimum,
+        maximum,
+        closed,
+    };
+    shape
+}
+
+#[test]
+fn test_a_ray_misses_a_cylinder() {
+    let cyl = Cylinder::new();
+    let examples = [
+        (Tuple::point(1.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0)),
+        (Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0)),
+        (Tuple::point(0.0, 0.0, -5.0), Tuple::vector(1.0, 1.0, 1.0)),
+    ];
+    for (origin, direction) in examples.iter() {
+        let r = Ray::new(*origin, direction.normalize());
+        let xs = cyl.local_intersect(r);
+        assert!(xs.is_empty());
+    }
+}
+
+#[test]
+fn test_a_ray_strikes_a_cylinder() {
+    let cyl = Cylinder::new();
+    let examples = [
+        (
+            Tuple::point(1.0, 0.0, -5.0),
+            Tuple::vector(0.0, 0.0, 1.0),
+            5.0,
+            5.0,
+        ),
+        (
+            Tuple::point(0.0, 0.0, -5.0),
+            Tuple::vector(0.0, 0.0, 1.0),
+            4.0,
+            6.0,
+        ),
+        (
+            Tuple::point(0.5, 0.0, -5.0),
+            Tuple::vector(0.1, 1.0, 1.0),
+            6.80798,
+            7.08872,
+        ),
+    ];
+    for (origin, direction, t0, t1) in examples.iter() {
+        let r = Ray::new(*origin, direction.normalize());
+        let xs = cyl.local_intersect(r);
+        assert_eq!(xs.len(), 2);
+        assert!((xs[0].t - t0).abs() < 0.0001, "{} != {}", xs[0].t, t0);
+        assert!((xs[1].t - t1).abs() < 0.0001, "{} != {}", xs[1].t, t1);
+    }
+}
+
+#[test]
+fn test_the_normal_vector_on_a_cylinder() {
+    let cyl = Cylinder::new();
+    let examples = [
+        (Tuple::point(1.0, 0.0, 0.0), Tuple::vector(1.0, 0.0, 0.0)),
+        (Tuple::point(0.0, 5.0, -1.0), Tuple::vector(0.0, 0.0, -1.0)),
+        (Tuple::point(0.0, -2.0, 1.0), Tuple::vector(0.0, 0.0, 1.0)),
+        (Tuple::point(-1.0, 1.0, 0.0), Tuple::vector(-1.0, 0.0, 0.0)),
+    ];
+    for (point, normal) in examples.iter() {
+        assert_eq!(cyl.local_normal_at(*point), *normal);
+    }
+}
+
+#[test]
+fn test_the_default_minimum_and_maximum_for_a_cylinder() {
+    let cyl = Cylinder::new();
+    match cyl.shape_kind {
+        ShapeKind::Cylinder {
+            minimum,
+            maximum,
+            closed,
+        } => {
+            assert_eq!(minimum, -f32::INFINITY);
+            assert_eq!(maximum, f32::INFINITY);
+            assert!(!closed);
+        }
+        _ => panic!("expected a cylinder"),
+    }
+}
+
+#[test]
+fn test_intersecting_a_constrained_cylinder() {
+    let cyl = truncated_cylinder(1.0, 2.0, false);
+    let examples = [
+        (Tuple::point(0.0, 1.5, 0.0), Tuple::vector(0.1, 1.0, 0.0), 0),
+        (
+            Tuple::point(0.0, 3.0, -5.0),
+            Tuple::vector(0.0, 0.0, 1.0),
+            0,
+        ),
+        (
+            Tuple::point(0.0, 0.0, -5.0),
+            Tuple::vector(0.0, 0.0, 1.0),
+            0,
+        ),
+        (
+            Tuple::point(0.0, 2.0, -5.0),
+            Tuple::vector(0.0, 0.0, 1.0),
+            0,
+        ),
+        (
+            Tuple::point(0.0, 1.0, -5.0),
+            Tuple::vector(0.0, 0.0, 1.0),
+            0,
+        ),
+        (
+            Tuple::point(0.0, 1.5, -2.0),
+            Tuple::vector(0.0, 0.0, 1.0),
+            2,
+        ),
+    ];
+    for (point, direction, count) in examples.iter() {
+        let r = Ray::new(*point, direction.normalize());
+        let xs = cyl.local_intersect(r);
+        assert_eq!(xs.len(), *count);
+    }
+}
+
+#[test]
+fn test_intersecting_the_caps_of_a_closed_cylinder() {
+    let cyl = truncated_cylinder(1.0, 2.0, true);
+    let examples = [
+        (
+            Tuple::point(0.0, 3.0, 0.0),
+            Tuple::vector(0.0, -1.0, 0.0),
+            2,
+        ),
+        (
+            Tuple::point(0.0, 3.0, -2.0),
+            Tuple::vector(0.0, -1.0, 2.0),
+            2,
+        ),
+        (
+            Tuple::point(0.0, 4.0, -2.0),
+            Tuple::vector(0.0, -1.0, 1.0),
+            2,
+        ),
+        (
+            Tuple::point(0.0, 0.0, -2.0),
+            Tuple::vector(0.0, 1.0, 2.0),
+            2,
+        ),
+        (
+            Tuple::point(0.0, -1.0, -2.0),
+            Tuple::vector(0.0, 1.0, 1.0),
+            2,
+        ),
+    ];
+    for (point, direction, count) in examples.iter() {
+        let r = Ray::new(*point, direction.normalize());
+        let xs = cyl.local_intersect(r);
+        assert_eq!(xs.len(), *count);
+    }
+}
+
+#[test]
+fn test_the_normal_vector_on_a_cylinders_end_caps() {
+    let cyl = truncated_cylinder(1.0, 2.0, true);
+    let examples = [
+        (Tuple::point(0.0, 1.0, 0.0), Tuple::vector(0.0, -1.0, 0.0)),
+        (Tuple::point(0.5, 1.0, 0.0), Tuple::vector(0.0, -1.0, 0.0)),
+        (Tuple::point(0.0, 1.0, 0.5), Tuple::vector(0.0, -1.0, 0.0)),
+        (Tuple::point(0.0, 2.0, 0.0), Tuple::vector(0.0, 1.0, 0.0)),
+        (Tuple::point(0.5, 2.0, 0.0), Tuple::vector(0.0, 1.0, 0.0)),
+        (Tuple::point(0.0, 2.0, 0.5), Tuple::vector(0.0, 1.0, 0.0)),
+    ];
+    for (point, normal) in examples.iter() {
+        assert_eq!(cyl.local_normal_at(*point), *normal);
+    }
+}
+
+pub struct Cone {}
+
+impl Cone {
+    pub fn new() -> Shape {
+        let mut shape = Shape::default();
+        shape.shape_kind = ShapeKind::Cone {
+            minimum: -f32::INFINITY,
+            maximum: f32::INFINITY,
+            closed: false,
+        };
+        shape
+    }
+}
+
+#[test]
+fn test_intersecting_a_cone_with_a_ray() {
+    let shape = Cone::new();
+    let examples = [
+        (
+            Tuple::point(0.0, 0.0, -5.0),
+            Tuple::vector(0.0, 0.0, 1.0),
+            5.0,
+            5.0,
+        ),
+        (
+            Tuple::point(0.0, 0.0, -5.0),
+            Tuple::vector(1.0, 1.0, 1.0),
+            8.66025,
+            8.66025,
+        ),
+        (
+            Tuple::point(1.0, 1.0, -5.0),
+            Tuple::vector(-0.5, -1.0, 1.0),
+            4.55006,
+            49.44994,
+        ),
+    ];
+    for (origin, direction, t0, t1) in examples.iter() {
+        let r = Ray::new(*origin, direction.normalize());
+        let xs = shape.local_intersect(r);
+        assert_eq!(xs.len(), 2);
+        assert!((xs[0].t - t0).abs() < 0.001, "{} != {}", xs[0].t, t0);
+        assert!((xs[1].t - t1).abs() < 0.001, "{} != {}", xs[1].t, t1);
+    }
+}
+
+#[test]
+fn test_intersecting_a_cone_with_a_ray_parallel_to_one_of_its_halves() {
+    let shape = Cone::new();
+    let direction = Tuple::vector(0.0, 1.0, 1.0).normalize();
+    let r = Ray::new(Tuple::point(0.0, 0.0, -1.0), direction);
+    let xs = shape.local_intersect(r);
+    assert_eq!(xs.len(), 1);
+    assert!((xs[0].t - 0.35355).abs() < 0.0001);
+}
+
+#[test]
+fn test_intersecting_a_cones_end_caps() {
+    let mut shape = Cone::new();
+    shape.shape_kind = ShapeKind::Cone {
+        minimum: -0.5,
+        maximum: 0.5,
+        closed: true,
+    };
+    let examples = [
+        (
+            Tuple::point(0.0, 0.0, -5.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+            0,
+        ),
+        (
+            Tuple::point(0.0, 0.0, -0.25),
+            Tuple::vector(0.0, 1.0, 1.0),
+            2,
+        ),
+        (
+            Tuple::point(0.0, 0.0, -0.25),
+            Tuple::vector(0.0, 1.0, 0.0),
+            4,
+        ),
+    ];
+    for (origin, direction, count) in examples.iter() {
+        let r = Ray::new(*origin, direction.normalize());
+        let xs = shape.local_intersect(r);
+        assert_eq!(xs.len(), *count);
+    }
+}
+
+#[test]
+fn test_computing_the_normal_vector_on_a_cone() {
+    let shape = Cone::new();
+    let examples = [
+        (Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 0.0)),
+        (
+            Tuple::point(1.0, 1.0, 1.0),
+            Tuple::vector(1.0, -(2f32.sqrt()), 1.0),
+        ),
+        (Tuple::point(-1.0, -1.0, 0.0), Tuple::vector(-1.0, 1.0, 0.0)),
+    ];
+    for (point, normal) in examples.iter() {
+        assert_eq!(shape.local_normal_at(*point), *normal);
+    }
+}
+
+pub struct Triangle {}
+
+impl Triangle {
+    pub fn new(p1: Tuple, p2: Tuple, p3: Tuple) -> Shape {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+        let normal = e2.cross(e1).normalize();
+        let mut shape = Shape::default();
+        shape.shape_kind = ShapeKind::Triangle {
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal,
+        };
+        shape
+    }
+}
+
+#[cfg(test)]
+fn test_triangle() -> Shape {
+    Triangle::new(
+        Tuple::point(0.0, 1.0, 0.0),
+        Tuple::point(-1.0, 0.0, 0.0),
+        Tuple::point(1.0, 0.0, 0.0),
+    )
+}
+
+#[test]
+fn test_constructing_a_triangle() {
+    let p1 = Tuple::point(0.0, 1.0, 0.0);
+    let p2 = Tuple::point(-1.0, 0.0, 0.0);
+    let p3 = Tuple::point(1.0, 0.0, 0.0);
+    let t = Triangle::new(p1, p2, p3);
+    match t.shape_kind {
+        ShapeKind::Triangle {
+            p1: tp1,
+            p2: tp2,
+            p3: tp3,
+            e1,
+            e2,
+            normal,
+        } => {
+            assert_eq!(tp1, p1);
+            assert_eq!(tp2, p2);
+            assert_eq!(tp3, p3);
+            assert_eq!(e1, Tuple::vector(-1.0, -1.0, 0.0));
+            assert_eq!(e2, Tuple::vector(1.0, -1.0, 0.0));
+            assert_eq!(normal, Tuple::vector(0.0, 0.0, -1.0));
+        }
+        _ => panic!("expected a triangle"),
+    }
+}
+
+#[test]
+fn test_finding_the_normal_on_a_triangle() {
+    let t = test_triangle();
+    let normal = Tuple::vector(0.0, 0.0, -1.0);
+    assert_eq!(t.local_normal_at(Tuple::point(0.0, 0.5, 0.0)), normal);
+    assert_eq!(t.local_normal_at(Tuple::point(-0.5, 0.75, 0.0)), normal);
+    assert_eq!(t.local_normal_at(Tuple::point(0.5, 0.25, 0.0)), normal);
+}
+
+#[test]
+fn test_intersecting_a_ray_parallel_to_the_triangle() {
+    let t = test_triangle();
+    let r =
+        Ray::new(Tuple::point(0.0, -1.0, -2.0), Tuple::vector(0.0, 1.0, 0.0));
+    let xs = t.local_intersect(r);
+    assert!(xs.is_empty());
+}
+
+#[test]
+fn test_a_ray_misses_the_p1_p3_edge() {
+    let t = test_triangle();
+    let r =
+        Ray::new(Tuple::point(1.0, 1.0, -2.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = t.local_intersect(r);
+    assert!(xs.is_empty());
+}
+
+#[test]
+fn test_a_ray_misses_the_p1_p2_edge() {
+    let t = test_triangle();
+    let r =
+        Ray::new(Tuple::point(-1.0, 1.0, -2.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = t.local_intersect(r);
+    assert!(xs.is_empty());
+}
+
+#[test]
+fn test_a_ray_misses_the_p2_p3_edge() {
+    let t = test_triangle();
+    let r =
+        Ray::new(Tuple::point(0.0, -1.0, -2.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = t.local_intersect(r);
+    assert!(xs.is_empty());
+}
+
+#[test]
+fn test_a_ray_strikes_a_triangle() {
+    let t = test_triangle();
+    let r =
+        Ray::new(Tuple::point(0.0, 0.5, -2.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = t.local_intersect(r);
+    assert_eq!(xs.len(), 1);
+    assert_eq!(xs[0].t, 2.0);
+}
+
+pub struct SmoothTriangle {}
+
+impl SmoothTriangle {
+    pub fn new(
+        p1: Tuple,
+        p2: Tuple,
+        p3: Tuple,
+        n1: Tuple,
+        n2: Tuple,
+        n3: Tuple,
+    ) -> Shape {
+        let mut shape = Shape::default();
+        shape.shape_kind = ShapeKind::SmoothTriangle {
+            p1,
+            p2,
+            p3,
+            n1,
+            n2,
+            n3,
+            e1: p2 - p1,
+            e2: p3 - p1,
+        };
+        shape
+    }
+}
+
+#[cfg(test)]
+fn test_smooth_triangle() -> Shape {
+    SmoothTriangle::new(
+        Tuple::point(0.0, 1.0, 0.0),
+        Tuple::point(-1.0, 0.0, 0.0),
+        Tuple::point(1.0, 0.0, 0.0),
+        Tuple::vector(0.0, 1.0, 0.0),
+        Tuple::vector(-1.0, 0.0, 0.0),
+        Tuple::vector(1.0, 0.0, 0.0),
+    )
+}
+
+#[test]
+fn test_constructing_a_smooth_triangle() {
+    let tri = test_smooth_triangle();
+    match tri.shape_kind {
+        ShapeKind::SmoothTriangle {
+            p1,
+            p2,
+            p3,
+            n1,
+            n2,
+            n3,
+            ..
+        } => {
+            assert_eq!(p1, Tuple::point(0.0, 1.0, 0.0));
+            assert_eq!(p2, Tuple::point(-1.0, 0.0, 0.0));
+            assert_eq!(p3, Tuple::point(1.0, 0.0, 0.0));
+            assert_eq!(n1, Tuple::vector(0.0, 1.0, 0.0));
+            assert_eq!(n2, Tuple::vector(-1.0, 0.0, 0.0));
+            assert_eq!(n3, Tuple::vector(1.0, 0.0, 0.0));
+        }
+        _ => panic!("expected a smooth triangle"),
+    }
+}
+
+#[test]
+fn test_an_intersection_with_a_triangle_stores_u_and_v() {
+    let tri = test_smooth_triangle();
+    let r =
+        Ray::new(Tuple::point(-0.2, 0.3, -2.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = tri.local_intersect(r);
+    assert_eq!(xs.len(), 1);
+    assert!(float_eq(xs[0].u, 0.45));
+    assert!(float_eq(xs[0].v, 0.25));
+}
+
+#[test]
+fn test_a_smooth_triangle_uses_u_and_v_to_interpolate_the_normal() {
+    let tri = test_smooth_triangle();
+    let hit = Intersection::new_with_uv(1.0, tri.clone(), 0.45, 0.25);
+    let n = tri.normal_at_hit(Tuple::point(0.0, 0.0, 0.0), &hit);
+    assert_eq!(n, Tuple::vector(-0.5547, 0.83205, 0.0));
+}
+
+#[test]
+fn test_preparing_the_normal_on_a_smooth_triangle() {
+    let tri = test_smooth_triangle();
+    let mut hit = Intersection::new_with_uv(1.0, tri, 0.45, 0.25);
+    let r =
+        Ray::new(Tuple::point(-0.2, 0.3, -2.0), Tuple::vector(0.0, 0.0, 1.0));
+    hit.prepare_hit(&r);
+    assert_eq!(hit.normalv, Some(Tuple::vector(-0.5547, 0.83205, 0.0)));
+}
+
+pub struct Group {}
+
+impl Group {
+    pub fn new() -> Shape {
+        let mut shape = Shape::default();
+        shape.shape_kind = ShapeKind::Group {
+            children: vec![],
+            bounds: BoundingBox::default(),
+        };
+        shape
+    }
+}
+
+#[test]
+fn test_creating_a_new_group() {
+    let g = Group::new();
+    assert_eq!(g.transform, Matrix4::default());
+    assert!(g.children().is_empty());
+}
+
+#[test]
+fn test_adding_a_child_to_a_group() {
+    let mut g = Group::new();
+    let s = Sphere::new();
+    g.add_child(s.clone());
+    assert_eq!(g.children(), &[s]);
+}
+
+#[test]
+fn test_intersecting_a_ray_with_an_empty_group() {
+    let g = Group::new();
+    let r = Ray::new(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = g.local_intersect(r);
+    assert!(xs.is_empty());
+}
+
+#[test]
+fn test_intersecting_a_ray_with_a_nonempty_group() {
+    let mut g = Group::new();
+    let s1 = Sphere::new();
+    let mut s2 = Sphere::new();
+    s2.transform = Matrix4::translation(0.0, 0.0, -3.0);
+    let mut s3 = Sphere::new();
+    s3.transform = Matrix4::translation(5.0, 0.0, 0.0);
+    g.add_child(s1.clone());
+    g.add_child(s2.clone());
+    g.add_child(s3);
+    let r =
+        Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = g.local_intersect(r);
+    assert_eq!(xs.len(), 4);
+    assert_eq!(xs[0].object, s2);
+    assert_eq!(xs[1].object, s2);
+    assert_eq!(xs[2].object, s1);
+    assert_eq!(xs[3].object, s1);
+}
+
+#[test]
+fn test_intersecting_a_transformed_group() {
+    let mut g = Group::new();
+    g.transform = Matrix4::scaling(2.0, 2.0, 2.0);
+    let mut s = Sphere::new();
+    s.transform = Matrix4::translation(5.0, 0.0, 0.0);
+    g.add_child(s);
+    let r =
+        Ray::new(Tuple::point(10.0, 0.0, -10.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = g.intersect(&r);
+    assert_eq!(xs.len(), 2);
+}
+
+#[test]
+fn test_converting_a_point_from_world_to_object_space() {
+    let mut g1 = Group::new();
+    g1.transform = Matrix4::rotation_y(std::f32::consts::PI / 2.0);
+    let mut g2 = Group::new();
+    g2.transform = Matrix4::scaling(2.0, 2.0, 2.0);
+    let mut s = Sphere::new();
+    s.transform = Matrix4::translation(5.0, 0.0, 0.0);
+    g2.add_child(s);
+    g1.add_child(g2);
+    let r =
+        Ray::new(Tuple::point(-2.0, 0.0, -20.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = g1.intersect(&r);
+    assert_eq!(xs.len(), 2);
+    let p = xs[0].object.world_to_object(Tuple::point(-2.0, 0.0, -10.0));
+    assert_eq!(p, Tuple::point(0.0, 0.0, -1.0));
+}
+
+#[test]
+fn test_converting_a_normal_from_object_to_world_space() {
+    let mut g1 = Group::new();
+    g1.transform = Matrix4::rotation_y(std::f32::consts::PI / 2.0);
+    let mut g2 = Group::new();
+    g2.transform = Matrix4::scaling(1.0, 2.0, 3.0);
+    let mut s = Sphere::new();
+    s.transform = Matrix4::translation(5.0, 0.0, 0.0);
+    g2.add_child(s);
+    g1.add_child(g2);
+    let r =
+        Ray::new(Tuple::point(-2.0, 0.0, -20.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = g1.intersect(&r);
+    let object = &xs[0].object;
+    let n = object.normal_to_world(Tuple::vector(
+        3f32.sqrt() / 3.0,
+        3f32.sqrt() / 3.0,
+        3f32.sqrt() / 3.0,
+    ));
+    assert_eq!(n, Tuple::vector(0.28571, 0.42857, -0.85714));
+}
+
+#[test]
+fn test_finding_the_normal_on_a_child_object() {
+    let mut g1 = Group::new();
+    g1.transform = Matrix4::rotation_y(std::f32::consts::PI / 2.0);
+    let mut g2 = Group::new();
+    g2.transform = Matrix4::scaling(1.0, 2.0, 3.0);
+    let mut s = Sphere::new();
+    s.transform = Matrix4::translation(5.0, 0.0, 0.0);
+    g2.add_child(s);
+    g1.add_child(g2);
+    let r =
+        Ray::new(Tuple::point(-2.0, 0.0, -20.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = g1.intersect(&r);
+    let n = xs[0]
+        .object
+        .normal_at(Tuple::point(1.7321, 1.1547, -5.5774));
+    assert_eq!(n, Tuple::vector(0.2857, 0.42854, -0.85716));
+}
+
+pub struct Csg {}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Shape, right: Shape) -> Shape {
+        let mut shape = Shape::default();
+        let bounds = csg_bounds(&left, &right);
+        shape.shape_kind = ShapeKind::Csg {
+            operation,
+            left: Box::new(left),
+            right: Box::new(right),
+            bounds,
+        };
+        shape
+    }
+}
+
+fn csg_bounds(left: &Shape, right: &Shape) -> BoundingBox {
+    let mut bounds = left.parent_space_bounds();
+    bounds.merge(&right.parent_space_bounds());
+    bounds
+}
+
+#[test]
+fn test_csg_is_created_with_an_operation_and_two_shapes() {
+    let s1 = Sphere::new();
+    let s2 = Cube::new();
+    let c = Csg::new(CsgOperation::Union, s1.clone(), s2.clone());
+    assert_eq!(
+        c.shape_kind,
+        ShapeKind::Csg {
+            operation: CsgOperation::Union,
+            left: Box::new(s1),
+            right: Box::new(s2),
+            bounds: BoundingBox::new(
+                Tuple::point(-1.0, -1.0, -1.0),
+                Tuple::point(1.0, 1.0, 1.0),
+            ),
+        }
+    );
+}
+
+#[test]
+fn test_evaluating_the_rule_for_a_csg_operation() {
+    use self::CsgOperation::*;
+
+    let examples = [
+        (Union, true, true, true, false),
+        (Union, true, true, false, true),
+        (Union, true, false, true, false),
+        (Union, true, false, false, true),
+        (Union, false, true, true, false),
+        (Union, false, true, false, false),
+        (Union, false, false, true, true),
+        (Union, false, false, false, true),
+        (Intersection, true, true, true, true),
+        (Intersection, true, true, false, false),
+        (Intersection, true, false, true, true),
+        (Intersection, true, false, false, false),
+        (Intersection, false, true, true, true),
+        (Intersection, false, true, false, true),
+        (Intersection, false, false, true, false),
+        (Intersection, false, false, false, false),
+        (Difference, true, true, true, false),
+        (Difference, true, true, false, true),
+        (Difference, true, false, true, false),
+        (Difference, true, false, false, true),
+        (Difference, false, true, true, true),
+        (Difference, false, true, false, true),
+        (Difference, false, false, true, false),
+        (Difference, false, false, false, false),
+    ];
+    for (operation, lhit, inl, inr, result) in examples.iter() {
+        assert_eq!(
+            intersection_allowed(*operation, *lhit, *inl, *inr),
+            *result,
+            "{:?} lhit: {} inl: {} inr: {}",
+            operation,
+            lhit,
+            inl,
+            inr
+        );
+    }
+}
+
+#[test]
+fn test_filtering_a_list_of_intersections() {
+    let s1 = Sphere::new();
+    let s2 = Cube::new();
+    let examples = [
+        (CsgOperation::Union, 0, 3),
+        (CsgOperation::Intersection, 1, 2),
+        (CsgOperation::Difference, 0, 1),
+    ];
+    for (operation, x0, x1) in examples.iter() {
+        let xs = vec![
+            (true, Intersection::new(1.0, s1.clone())),
+            (false, Intersection::new(2.0, s2.clone())),
+            (true, Intersection::new(3.0, s1.clone())),
+            (false, Intersection::new(4.0, s2.clone())),
+        ];
+        let expected = vec![xs[*x0].1.clone(), xs[*x1].1.clone()];
+        let result = filter_intersections(*operation, xs);
+        assert_eq!(result, expected);
+    }
+}
+
+#[test]
+fn test_a_ray_misses_a_csg_object() {
+    let c = Csg::new(CsgOperation::Union, Sphere::new(), Cube::new());
+    let r =
+        Ray::new(Tuple::point(0.0, 2.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = c.local_intersect(r);
+    assert!(xs.is_empty());
+}
+
+#[test]
+fn test_a_ray_hits_a_csg_object() {
+    let s1 = Sphere::new();
+    let mut s2 = Sphere::new();
+    s2.transform = Matrix4::translation(0.0, 0.0, 0.5);
+    let c = Csg::new(CsgOperation::Union, s1.clone(), s2.clone());
+    let r =
+        Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+    let xs = c.local_intersect(r);
+    assert_eq!(xs.len(), 2);
+    assert_eq!(xs[0].t, 4.0);
+    assert_eq!(xs[0].object, s1);
+    assert_eq!(xs[1].t, 6.5);
+    assert_eq!(xs[1].object, s2);
+}
+
+#[test]
+fn test_a_csg_difference_cuts_a_hole() {
+    let mut inner = truncated_cylinder(-2.0, 2.0, true);
+    inner.transform = Matrix4::scaling(0.5, 1.0, 0.5);
+    let mut c = Csg::new(CsgOperation::Difference, Cube::new(), inner);
+    c.transform = Matrix4::translation(0.0, 0.0, 1.0);
+    let r =
+        Ray::new(Tuple::point(0.0, 2.0, 1.0), Tuple::vector(0.0, -1.0, 0.0));
+    assert!(c.intersect(&r).is_empty());
+    let r =
+        Ray::new(Tuple::point(0.75, 2.0, 1.0), Tuple::vector(0.0, -1.0, 0.0));
+    let xs = c.intersect(&r);
+    assert_eq!(xs.len(), 2);
+    assert_eq!(xs[0].t, 1.0);
+    assert_eq!(xs[1].t, 3.0);
+    assert_eq!(xs[0].object.transform, Matrix4::translation(0.0, 0.0, 1.0));
+}
+
+#[test]
+fn test_the_bounds_of_primitive_shapes() {
+    let unit = BoundingBox::new(
+        Tuple::point(-1.0, -1.0, -1.0),
+        Tuple::point(1.0, 1.0, 1.0),
+    );
+    assert_eq!(Sphere::new().bounds(), unit);
+    assert_eq!(Cube::new().bounds(), unit);
+    let plane = Plane::new().bounds();
+    assert_eq!(plane.min.x, -f32::INFINITY);
+    assert_eq!(plane.max.z, f32::INFINITY);
+    assert_eq!(plane.min.y, 0.0);
+    assert_eq!(plane.max.y, 0.0);
+    assert_eq!(
+        truncated_cylinder(-5.0, 3.0, true).bounds(),
+        BoundingBox::new(
+            Tuple::point(-1.0, -5.0, -1.0),
+            Tuple::point(1.0, 3.0, 1.0)
+        )
+    );
+    let mut cone = Cone::new();
+    cone.shape_kind = ShapeKind::Cone {
+        minimum: -5.0,
+        maximum: 3.0,
+        closed: false,
+    };
+    assert_eq!(
+        cone.bounds(),
+        BoundingBox::new(
+            Tuple::point(-5.0, -5.0, -5.0),
+            Tuple::point(5.0, 3.0, 5.0)
+        )
+    );
+    let triangle = Triangle::new(
+        Tuple::point(-3.0, 7.0, 2.0),
+        Tuple::point(6.0, 2.0, -4.0),
+        Tuple::point(2.0, -1.0, -1.0),
+    );
+    assert_eq!(
+        triangle.bounds(),
+        BoundingBox::new(
+            Tuple::point(-3.0, -1.0, -4.0),
+            Tuple::point(6.0, 7.0, 2.0)
+        )
+    );
+}
+
+#[test]
+fn test_a_group_has_a_bounding_box_that_contains_its_children() {
+    let mut s = Sphere::new();
+    s.transform =
+        Matrix4::translation(2.0, 5.0, -3.0) * Matrix4::scaling(2.0, 2.0, 2.0);
+    let mut c = truncated_cylinder(-2.0, 2.0, false);
+    c.transform =
+        Matrix4::translation(-4.0, -1.0, 4.0) * Matrix4::scaling(0.5, 1.0, 0.5);
+    let mut g = Group::new();
+    g.add_child(s);
+    g.add_child(c);
+    assert_eq!(
+        g.bounds(),
+        BoundingBox::new(
+            Tuple::point(-4.5, -3.0, -5.0),
+            Tuple::point(4.0, 7.0, 4.5)
+        )
+    );
+}
+
+#[test]
+fn test_a_csg_shape_has_a_bounding_box_that_contains_its_children() {
+    let left = Sphere::new();
+    let mut right = Sphere::new();
+    right.transform = Matrix4::translation(2.0, 3.0, 4.0);
+    let c = Csg::new(CsgOperation::Difference, left, right);
+    assert_eq!(
+        c.bounds(),
+        BoundingBox::new(
+            Tuple::point(-1.0, -1.0, -1.0),
+            Tuple::point(3.0, 4.0, 5.0)
+        )
+    );
+}
+
+#[test]
+fn test_querying_a_shapes_bounding_box_in_its_parents_space() {
+    let mut s = Sphere::new();
+    s.transform =
+        Matrix4::translation(1.0, -3.0, 5.0) * Matrix4::scaling(0.5, 2.0, 4.0);
+    assert_eq!(
+        s.parent_space_bounds(),
+        BoundingBox::new(
+            Tuple::point(0.5, -5.0, 1.0),
+            Tuple::point(1.5, -1.0, 9.0)
+        )
+    );
+}
+
+#[test]
+fn test_intersecting_a_group_skips_children_when_the_box_is_missed() {
+    let mut g = Group::new();
+    g.add_child(Sphere::new());
+    let r =
+        Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 1.0, 0.0));
+    assert!(!g.bounds().intersects(&r));
+    assert!(g.intersect(&r).is_empty());
+}
+
+#[test]
+fn test_partitioning_a_groups_children() {
+    let mut s1 = Sphere::new();
+    s1.transform = Matrix4::translation(-2.0, 0.0, 0.0);
+    let mut s2 = Sphere::new();
+    s2.transform = Matrix4::translation(2.0, 0.0, 0.0);
+    let s3 = Sphere::new();
+    let p = Plane::new();
+    let mut children = vec![s1.clone(), s2.clone(), s3.clone(), p.clone()];
+    let (left, right) = partition_children(&mut children).unwrap();
+    assert_eq!(children, vec![p]);
+    assert_eq!(left, vec![s1]);
+    assert_eq!(right, vec![s2, s3]);
+}
+
+#[test]
+fn test_partitioning_children_that_cannot_be_split() {
+    let mut children = vec![Sphere::new(), Sphere::new(), Plane::new()];
+    assert!(partition_children(&mut children).is_none());
+    assert_eq!(children.len(), 3);
+}
+
+#[test]
+fn test_subdividing_a_group_partitions_its_children() {
+    let mut s1 = Sphere::new();
+    s1.transform = Matrix4::translation(-4.0, 0.0, 0.0);
+    let mut s2 = Sphere::new();
+    s2.transform = Matrix4::translation(-4.0, 0.0, 1.0);
+    let mut s3 = Sphere::new();
+    s3.transform = Matrix4::scaling(4.0, 4.0, 4.0);
+    let mut g = Group::new();
+    g.add_child(s1.clone());
+    g.add_child(s2.clone());
+    g.add_child(s3.clone());
+    let bounds = g.bounds();
+    g.divide(1);
+    assert_eq!(g.bounds(), bounds);
+    assert_eq!(g.children().len(), 2);
+    assert_eq!(g.children()[1], s3);
+    let subgroup = &g.children()[0];
+    assert_eq!(subgroup.children(), &[s1, s2]);
+}
+
+#[test]
+fn test_subdividing_a_group_with_too_few_children() {
+    let mut s1 = Sphere::new();
+    s1.transform = Matrix4::translation(-2.0, 0.0, 0.0);
+    let mut s2 = Sphere::new();
+    s2.transform = Matrix4::translation(2.0, 1.0, 0.0);
+    let mut s3 = Sphere::new();
+    s3.transform = Matrix4::translation(2.0, -1.0, 0.0);
+    let mut sub = Group::new();
+    sub.add_child(s1.clone());
+    sub.add_child(s2.clone());
+    sub.add_child(s3.clone());
+    let s4 = Sphere::new();
+    let mut g = Group::new();
+    g.add_child(sub);
+    g.add_child(s4.clone());
+    g.divide(3);
+    assert_eq!(g.children().len(), 2);
+    assert_eq!(g.children()[1], s4);
+    let sub = &g.children()[0];
+    assert_eq!(sub.children().len(), 2);
+    assert_eq!(sub.children()[0], s1);
+    assert_eq!(sub.children()[1].children(), &[s2, s3]);
+}
+
+#[test]
+fn test_subdividing_a_csg_shape_subdivides_its_children() {
+    let mut s1 = Sphere::new();
+    s1.transform = Matrix4::translation(-4.0, 0.0, 0.0);
+    let mut s2 = Sphere::new();
+    s2.transform = Matrix4::translation(-4.0, 0.0, 1.0);
+    let mut s3 = Sphere::new();
+    s3.transform = Matrix4::translation(4.0, 0.0, 0.0);
+    let mut left = Group::new();
+    left.add_child(s1.clone());
+    left.add_child(s2.clone());
+    left.add_child(s3.clone());
+    let mut s4 = Sphere::new();
+    s4.transform = Matrix4::translation(0.0, 0.0, -4.0);
+    let mut s5 = Sphere::new();
+    s5.transform = Matrix4::translation(1.0, 0.0, -4.0);
+    let mut s6 = Sphere::new();
+    s6.transform = Matrix4::translation(0.0, 0.0, 4.0);
+    let mut right = Group::new();
+    right.add_child(s4.clone());
+    right.add_child(s5.clone());
+    right.add_child(s6.clone());
+    let mut c = Csg::new(CsgOperation::Difference, left, right);
+    c.divide(3);
+    match c.shape_kind {
+        ShapeKind::Csg { left, right, .. } => {
+            assert_eq!(left.children().len(), 2);
+            assert_eq!(left.children()[0].children(), &[s1, s2]);
+            assert_eq!(left.children()[1], s3);
+            assert_eq!(right.children().len(), 2);
+            assert_eq!(right.children()[0].children(), &[s4, s5]);
+            assert_eq!(right.children()[1], s6);
+        }
+        _ => panic!("expected a CSG shape"),
+    }
+}
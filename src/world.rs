@@ -1,20 +1,35 @@
+use background::Background;
 use intersections::{find_hit, Intersection};
-use lighting::PointLight;
+use lighting::{
+    AmbientOcclusion, EnvironmentLight, Light, LightSample, PointLight,
+};
+#[cfg(test)]
+use lighting::{AreaLight, DirectionalLight};
 use matrices::Matrix4;
+use patterns::pattern_at_shape;
+#[cfg(test)]
+use random;
+use random::{cosine_weighted_direction, phong_lobe_direction};
 use rays::Ray;
-use shapes::{Shape, Sphere};
+use shapes::{Group, Shape, Sphere};
 use tuples::Tuple;
 
 pub struct World {
-    pub light_source: Option<PointLight>,
+    pub lights: Vec<Light>,
     pub objects: Vec<Shape>,
+    pub background: Background,
+    pub environment_light: Option<EnvironmentLight>,
+    pub ambient_occlusion: Option<AmbientOcclusion>,
 }
 
 impl World {
     pub fn new() -> Self {
         World {
-            light_source: None,
+            lights: vec![],
             objects: vec![],
+            background: Background::default(),
+            environment_light: None,
+            ambient_occlusion: None,
         }
     }
 
@@ -22,6 +37,20 @@ impl World {
         self.objects.push(shape);
     }
 
+    pub fn add_light<L: Into<Light>>(&mut self, light: L) {
+        self.lights.push(light.into());
+    }
+
+    /// Groups the objects into a bounding volume hierarchy, once built.
+    pub fn divide(&mut self, threshold: usize) {
+        let mut root = Group::new();
+        for object in self.objects.drain(..) {
+            root.add_child(object);
+        }
+        root.divide(threshold);
+        self.objects = root.children().to_vec();
+    }
+
     pub fn intersect_world(&self, ray: &Ray) -> Vec<Intersection> {
         let mut intersections = self
             .objects
@@ -35,27 +64,137 @@ impl World {
     pub fn color_at(&self, ray: &Ray, remaining: i32) -> Tuple {
         let xs = self.intersect_world(ray);
         if let Some(mut hit) = find_hit(&xs) {
-            hit.prepare_hit(ray);
+            hit.prepare_hit_with_intersections(ray, &xs);
             hit.shade_hit(self, remaining) // .normalize()
         } else {
-            Tuple::color(0.0, 0.0, 0.0)
+            self.background.color_at(ray.direction)
         }
     }
 
-    pub fn is_shadowed(&self, point: Tuple) -> bool {
-        if let Some(light) = self.light_source {
-            let v = light.position - point;
-            let distance = v.magnitude();
-            let ray = Ray::new(point, v.normalize());
-            let xs = self.intersect_world(&ray);
-            if let Some(hit) = find_hit(&xs) {
-                hit.t < distance
-            } else {
-                false
+    /// Whether no light at all gets from `light_position` to `point`.
+    pub fn is_shadowed(&self, light_position: Tuple, point: Tuple) -> bool {
+        let v = light_position - point;
+        self.shadow_color(point, v.normalize(), v.magnitude())
+            == Tuple::color(0.0, 0.0, 0.0)
+    }
+
+    /// The colour to multiply `light` by at `point`, after shadows.
+    pub fn intensity_at(&self, light: Light, point: Tuple) -> Tuple {
+        let samples = self.light_samples(light, point);
+        let total = samples
+            .iter()
+            .fold(Tuple::color(0.0, 0.0, 0.0), |sum, sample| {
+                sum + sample.intensity
+            });
+        total / samples.len() as f32
+    }
+
+    /// Each sample seen from `point`, shadowed along its own direction.
+    pub fn light_samples(
+        &self,
+        light: Light,
+        point: Tuple,
+    ) -> Vec<LightSample> {
+        light
+            .light_samples(point)
+            .into_iter()
+            .map(|sample| LightSample {
+                intensity: self.shadow_color(
+                    point,
+                    sample.direction,
+                    sample.distance,
+                ),
+                ..sample
+            })
+            .collect()
+    }
+
+    /// The open fraction of the hemisphere above `point`.
+    pub fn ambient_occlusion_at(&self, point: Tuple, normalv: Tuple) -> f32 {
+        let occlusion = match self.ambient_occlusion {
+            Some(occlusion) if occlusion.samples > 0 => occlusion,
+            _ => return 1.0,
+        };
+        let escaped = (0..occlusion.samples)
+            .filter(|_| {
+                let ray = Ray::new(point, cosine_weighted_direction(normalv));
+                let xs = self
+                    .intersect_world(&ray)
+                    .into_iter()
+                    .filter(|hit| hit.object.casts_shadow)
+                    .collect::<Vec<_>>();
+                match find_hit(&xs) {
+                    Some(hit) => hit.t >= occlusion.distance,
+                    None => true,
+                }
+            })
+            .count();
+        escaped as f32 / occlusion.samples as f32
+    }
+
+    /// Diffuse and specular light reaching `hit` from the background.
+    pub fn environment_lighting(&self, hit: &Intersection) -> Tuple {
+        let black = Tuple::color(0.0, 0.0, 0.0);
+        let environment = match self.environment_light {
+            Some(environment) if environment.samples > 0 => environment,
+            _ => return black,
+        };
+        let object = &hit.object;
+        let material = object.material;
+        let point = hit.over_point.unwrap();
+        let normalv = hit.normalv.unwrap();
+        let reflectv = (-hit.eyev.unwrap()).reflect(normalv);
+        let color = material
+            .pattern
+            .map(|pattern| pattern_at_shape(pattern, object, point))
+            .unwrap_or(material.color);
+        let incoming = |direction: Tuple| {
+            if !object.receives_shadow {
+                return self.background.color_at(direction);
+            }
+            self.background.color_at(direction)
+                * self.shadow_color(point, direction, f32::INFINITY)
+        };
+        let mut diffuse = black;
+        let mut specular = black;
+        for _ in 0..environment.samples {
+            if material.diffuse > 0.0 {
+                diffuse =
+                    diffuse + incoming(cosine_weighted_direction(normalv));
+            }
+            if material.specular > 0.0 {
+                let direction =
+                    phong_lobe_direction(reflectv, material.shininess);
+                if direction.dot(normalv) > 0.0 {
+                    specular = specular + incoming(direction);
+                }
             }
-        } else {
-            false
         }
+        (color * diffuse * material.diffuse + specular * material.specular)
+            * environment.intensity
+            / environment.samples as f32
+    }
+
+    /// The light left after passing through any transparent objects.
+    fn shadow_color(
+        &self,
+        point: Tuple,
+        direction: Tuple,
+        distance: f32,
+    ) -> Tuple {
+        let ray = Ray::new(point, direction);
+        let mut color = Tuple::color(1.0, 1.0, 1.0);
+        for hit in self.intersect_world(&ray) {
+            if hit.t <= 0.01 || hit.t >= distance || !hit.object.casts_shadow {
+                continue;
+            }
+            let material = hit.object.material;
+            if material.transparency <= 0.0 {
+                return Tuple::color(0.0, 0.0, 0.0);
+            }
+            color = color * material.color * material.transparency;
+        }
+        color
     }
 }
 
@@ -66,7 +205,7 @@ use REFLECTION_RECURSION_LIMIT;
 fn test_creating_a_world() {
     let w = World::new();
     assert_eq!(w.objects.len(), 0);
-    assert!(w.light_source.is_none());
+    assert!(w.lights.is_empty());
 }
 
 #[test]
@@ -91,6 +230,21 @@ fn test_the_color_a_ray_misses() {
     assert_eq!(c, Tuple::color(0.0, 0.0, 0.0));
 }
 
+#[test]
+fn test_the_color_a_ray_misses_comes_from_the_background() {
+    let world = World {
+        background: Background::Gradient {
+            bottom: Tuple::color(1.0, 1.0, 1.0),
+            top: Tuple::color(0.0, 0.0, 1.0),
+        },
+        ..World::default()
+    };
+    let ray =
+        Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 1.0, 0.0));
+    let c = world.color_at(&ray, REFLECTION_RECURSION_LIMIT);
+    assert_eq!(c, Tuple::color(0.0, 0.0, 1.0));
+}
+
 #[test]
 fn test_the_color_when_a_ray_hits() {
     let w = World::default();
@@ -117,10 +271,11 @@ fn test_color_at_with_mutually_reflective_surfaces() {
     use shapes::Plane;
 
     let mut world = World::default();
-    world.light_source = Some(PointLight::new(
+    world.lights = vec![PointLight::new(
         Tuple::point(0.0, 0.0, 0.0),
         Tuple::color(1.0, 1.0, 1.0),
-    ));
+    )
+    .into()];
 
     let mut lower = Plane::new();
     lower.material.reflective = 1.0;
@@ -142,28 +297,290 @@ fn test_color_at_with_mutually_reflective_surfaces() {
 fn test_there_is_no_shadow_when_nothing_is_collinear_with_point_and_light() {
     let world = World::default();
     let point = Tuple::point(0.0, 10.0, 0.0);
-    assert!(!world.is_shadowed(point));
+    assert!(!world.is_shadowed(world.lights[0].position().unwrap(), point));
 }
 
 #[test]
 fn test_shadow_when_an_object_is_between_the_point_and_the_light() {
     let world = World::default();
     let point = Tuple::point(10.0, -10.0, 10.0);
-    assert!(world.is_shadowed(point));
+    assert!(world.is_shadowed(world.lights[0].position().unwrap(), point));
 }
 
 #[test]
 fn test_there_is_no_shadow_when_an_object_is_behind_the_light() {
     let world = World::default();
     let point = Tuple::point(-20.0, 20.0, -20.0);
-    assert!(!world.is_shadowed(point));
+    assert!(!world.is_shadowed(world.lights[0].position().unwrap(), point));
 }
 
 #[test]
 fn test_there_is_no_shadow_when_an_object_is_behind_the_point() {
     let world = World::default();
     let point = Tuple::point(-2.0, 2.0, -2.0);
-    assert!(!world.is_shadowed(point));
+    assert!(!world.is_shadowed(world.lights[0].position().unwrap(), point));
+}
+
+#[test]
+fn test_objects_that_dont_cast_shadows_are_ignored_by_shadow_rays() {
+    let mut world = World::default();
+    let point = Tuple::point(10.0, -10.0, 10.0);
+    world.objects[0].casts_shadow = false;
+    assert!(world.is_shadowed(world.lights[0].position().unwrap(), point));
+    world.objects[1].casts_shadow = false;
+    assert!(!world.is_shadowed(world.lights[0].position().unwrap(), point));
+}
+
+#[test]
+fn test_a_group_that_doesnt_cast_shadows_hides_its_children() {
+    let mut world = World::default();
+    let mut group = Group::new();
+    for object in world.objects.drain(..) {
+        group.add_child(object);
+    }
+    group.casts_shadow = false;
+    world.add_shape(group);
+    let point = Tuple::point(10.0, -10.0, 10.0);
+    assert!(!world.is_shadowed(world.lights[0].position().unwrap(), point));
+}
+
+#[test]
+fn test_is_shadowed_tests_for_occlusion_between_two_points() {
+    let world = World::default();
+    let light_position = Tuple::point(-10.0, -10.0, -10.0);
+    let examples = [
+        (Tuple::point(-10.0, -10.0, 10.0), false),
+        (Tuple::point(10.0, 10.0, 10.0), true),
+        (Tuple::point(-20.0, -20.0, -20.0), false),
+        (Tuple::point(-5.0, -5.0, -5.0), false),
+    ];
+    for (point, result) in examples.iter() {
+        assert_eq!(world.is_shadowed(light_position, *point), *result);
+    }
+}
+
+#[test]
+fn test_a_transparent_object_doesnt_count_as_a_shadow() {
+    let mut world = World::default();
+    world.objects[0].material.transparency = 1.0;
+    world.objects[1].material.transparency = 1.0;
+    let light_position = world.lights[0].position().unwrap();
+    let point = Tuple::point(10.0, -10.0, 10.0);
+    assert!(!world.is_shadowed(light_position, point));
+    world.objects[0].material.color = Tuple::color(0.0, 0.0, 0.0);
+    assert!(world.is_shadowed(light_position, point));
+}
+
+#[test]
+fn test_point_lights_evaluate_the_light_intensity_at_a_given_point() {
+    let world = World::default();
+    let light = world.lights[0];
+    let examples = [
+        (Tuple::point(0.0, 1.0001, 0.0), 1.0),
+        (Tuple::point(-1.0001, 0.0, 0.0), 1.0),
+        (Tuple::point(0.0, 0.0, -1.0001), 1.0),
+        (Tuple::point(0.0, 0.0, 1.0001), 0.0),
+        (Tuple::point(1.0001, 0.0, 0.0), 0.0),
+        (Tuple::point(0.0, -1.0001, 0.0), 0.0),
+        (Tuple::point(0.0, 0.0, 0.0), 0.0),
+    ];
+    for (point, result) in examples.iter() {
+        assert_eq!(
+            world.intensity_at(light, *point),
+            Tuple::color(*result, *result, *result)
+        );
+    }
+}
+
+#[test]
+fn test_area_lights_evaluate_the_light_intensity_at_a_given_point() {
+    let world = World::default();
+    let light = AreaLight::new(
+        Tuple::point(-0.5, -0.5, -5.0),
+        Tuple::vector(1.0, 0.0, 0.0),
+        2,
+        Tuple::vector(0.0, 1.0, 0.0),
+        2,
+        Tuple::color(1.0, 1.0, 1.0),
+    );
+    let examples = [
+        (Tuple::point(0.0, 0.0, 2.0), 0.0),
+        (Tuple::point(1.0, -1.0, 2.0), 0.25),
+        (Tuple::point(1.5, 0.0, 2.0), 0.5),
+        (Tuple::point(1.25, 1.25, 3.0), 0.75),
+        (Tuple::point(0.0, 0.0, -2.0), 1.0),
+    ];
+    for (point, result) in examples.iter() {
+        assert_eq!(
+            world.intensity_at(light.into(), *point),
+            Tuple::color(*result, *result, *result)
+        );
+    }
+}
+
+#[test]
+fn test_jittered_light_samples_are_shadowed_along_their_own_direction() {
+    use shapes::Cube;
+
+    let mut world = World::new();
+    // A slab between the point and the -x half of the light
+    let mut slab = Cube::new();
+    slab.transform =
+        Matrix4::translation(-1.0, 5.0, 0.0) * Matrix4::scaling(1.0, 0.1, 1.0);
+    world.add_shape(slab);
+    let mut light = AreaLight::new(
+        Tuple::point(-1.0, 10.0, -0.5),
+        Tuple::vector(2.0, 0.0, 0.0),
+        4,
+        Tuple::vector(0.0, 0.0, 1.0),
+        4,
+        Tuple::color(1.0, 1.0, 1.0),
+    );
+    light.jitter = true;
+    let point = Tuple::point(0.0, 0.0, 0.0);
+    for sample in world.light_samples(light.into(), point) {
+        let blocked = sample.direction.x < 0.0;
+        let expected = if blocked { 0.0 } else { 1.0 };
+        assert_eq!(
+            sample.intensity,
+            Tuple::color(expected, expected, expected)
+        );
+    }
+}
+
+#[test]
+fn test_directional_lights_cast_shadows_from_any_distance() {
+    let world = World::default();
+    let light = Light::from(DirectionalLight::new(
+        Tuple::vector(0.0, -1.0, 0.0),
+        Tuple::color(1.0, 1.0, 1.0),
+    ));
+    let examples = [
+        (Tuple::point(0.0, -1.0001, 0.0), 0.0),
+        (Tuple::point(0.0, -1000.0, 0.0), 0.0),
+        (Tuple::point(0.0, 1.0001, 0.0), 1.0),
+        (Tuple::point(5.0, -1000.0, 0.0), 1.0),
+    ];
+    for (point, result) in examples.iter() {
+        assert_eq!(
+            world.intensity_at(light, *point),
+            Tuple::color(*result, *result, *result)
+        );
+    }
+}
+
+#[test]
+fn test_light_is_tinted_by_transparent_objects_it_passes_through() {
+    let mut world = World::default();
+    for object in world.objects.iter_mut() {
+        object.material.transparency = 0.5;
+    }
+    world.objects[0].material.color = Tuple::color(1.0, 0.5, 0.0);
+    let light = world.lights[0];
+    // Through both surfaces of each sphere
+    let point = Tuple::point(10.0, -10.0, 10.0);
+    assert_eq!(
+        world.intensity_at(light, point),
+        Tuple::color(0.0625, 0.015625, 0.0)
+    );
+    // Only on the way out of each sphere
+    let point = Tuple::point(0.0, 0.0, 0.0);
+    assert_eq!(
+        world.intensity_at(light, point),
+        Tuple::color(0.25, 0.125, 0.0)
+    );
+}
+
+#[test]
+fn test_ambient_occlusion_counts_rays_that_escape() {
+    use shapes::Plane;
+
+    let mut world = World::new();
+    world.ambient_occlusion = Some(AmbientOcclusion::new(32, 2.0));
+    world.add_shape(Plane::new());
+    let point = Tuple::point(0.0, 0.0001, 0.0);
+    let normalv = Tuple::vector(0.0, 1.0, 0.0);
+    assert_eq!(world.ambient_occlusion_at(point, normalv), 1.0);
+
+    // A ceiling further away than the maximum distance doesn't count
+    let mut ceiling = Plane::new();
+    ceiling.transform = Matrix4::translation(0.0, 3.0, 0.0);
+    world.add_shape(ceiling);
+    assert_eq!(world.ambient_occlusion_at(point, normalv), 1.0);
+
+    // Every ray out of a sphere hits it within the maximum distance
+    world.objects[1] = Sphere::new();
+    assert_eq!(world.ambient_occlusion_at(point, normalv), 0.0);
+
+    // Unless it doesn't cast shadows
+    world.objects[1].casts_shadow = false;
+    assert_eq!(world.ambient_occlusion_at(point, normalv), 1.0);
+}
+
+#[test]
+fn test_ambient_occlusion_is_off_by_default() {
+    let world = World::default();
+    let point = Tuple::point(0.0, 0.0, 0.0);
+    let normalv = Tuple::vector(0.0, 1.0, 0.0);
+    assert_eq!(world.ambient_occlusion_at(point, normalv), 1.0);
+}
+
+#[cfg(test)]
+fn environment_test_world(background: Background) -> World {
+    use shapes::Plane;
+
+    let mut floor = Plane::new();
+    floor.material.color = Tuple::color(1.0, 0.5, 0.5);
+    floor.material.ambient = 0.0;
+    floor.material.diffuse = 0.9;
+    floor.material.specular = 0.0;
+    World {
+        lights: vec![],
+        objects: vec![floor],
+        background,
+        environment_light: Some(EnvironmentLight::new(16)),
+        ambient_occlusion: None,
+    }
+}
+
+#[test]
+fn test_an_environment_light_lights_diffuse_surfaces_with_the_background() {
+    let world =
+        environment_test_world(Background::Color(Tuple::color(1.0, 1.0, 1.0)));
+    let ray =
+        Ray::new(Tuple::point(0.0, 1.0, -1.0), Tuple::vector(0.0, -1.0, 1.0));
+    let c = world.color_at(&ray, REFLECTION_RECURSION_LIMIT);
+    assert_eq!(c, Tuple::color(0.9, 0.45, 0.45));
+}
+
+#[test]
+fn test_objects_block_light_from_the_environment() {
+    let mut world =
+        environment_test_world(Background::Color(Tuple::color(1.0, 1.0, 1.0)));
+    let mut dome = Sphere::new();
+    dome.transform = Matrix4::scaling(10.0, 10.0, 10.0);
+    dome.material.color = Tuple::color(0.0, 0.0, 0.0);
+    world.add_shape(dome);
+    let ray =
+        Ray::new(Tuple::point(0.0, 1.0, -1.0), Tuple::vector(0.0, -1.0, 1.0));
+    let c = world.color_at(&ray, REFLECTION_RECURSION_LIMIT);
+    assert_eq!(c, Tuple::color(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn test_an_environment_light_gives_glossy_surfaces_specular_highlights() {
+    let mut world = environment_test_world(Background::Gradient {
+        bottom: Tuple::color(0.0, 0.0, 0.0),
+        top: Tuple::color(1.0, 1.0, 1.0),
+    });
+    world.objects[0].material.diffuse = 0.0;
+    world.objects[0].material.specular = 1.0;
+    world.objects[0].material.shininess = 1000.0;
+    let ray =
+        Ray::new(Tuple::point(0.0, 1.0, 0.0), Tuple::vector(0.0, -1.0, 0.0));
+    let c = world.color_at(&ray, REFLECTION_RECURSION_LIMIT);
+    // Nearly all of the reflection lobe points straight up, into white
+    assert!(c.red() > 0.99 && c.red() <= 1.0);
 }
 
 impl Default for World {
@@ -179,8 +596,11 @@ impl Default for World {
             Tuple::color(1.0, 1.0, 1.0),
         );
         World {
-            light_source: Some(light),
+            lights: vec![light.into()],
             objects: vec![sphere1, sphere2],
+            background: Background::default(),
+            environment_light: None,
+            ambient_occlusion: None,
         }
     }
 }
@@ -198,7 +618,87 @@ fn test_the_default_world() {
     s1.material.specular = 0.2;
     let mut s2 = Sphere::new();
     s2.transform = Matrix4::scaling(0.5, 0.5, 0.5);
-    assert_eq!(world.light_source, Some(light));
+    assert_eq!(world.lights, vec![light.into()]);
     assert!(world.objects.contains(&s1));
     assert!(world.objects.contains(&s2));
 }
+
+#[cfg(test)]
+fn random_scene_and_rays() -> (World, Vec<Ray>) {
+    use shapes::{Cube, Plane, Triangle};
+
+    // A fixed seed keeps the scene the same on every run
+    random::seed(2463534242);
+    let random = || random::random() * 2.0 - 1.0;
+    let mut world = World::default();
+    let mut floor = Plane::new();
+    floor.transform = Matrix4::translation(0.0, -10.0, 0.0);
+    world.add_shape(floor);
+    for i in 0..200 {
+        let mut shape = match i % 3 {
+            0 => Sphere::new(),
+            1 => Cube::new(),
+            _ => Triangle::new(
+                Tuple::point(random(), random(), random()),
+                Tuple::point(random(), random(), random()),
+                Tuple::point(random(), random(), random()),
+            ),
+        };
+        shape.transform = Matrix4::translation(
+            random() * 8.0,
+            random() * 8.0,
+            random() * 8.0,
+        ) * Matrix4::rotation_y(random() * 3.0)
+            * Matrix4::scaling(0.5, 0.5, 0.5);
+        world.add_shape(shape);
+    }
+    let rays = (0..200)
+        .map(|_| {
+            Ray::new(
+                Tuple::point(random() * 12.0, random() * 12.0, random() * 12.0),
+                Tuple::vector(random(), random(), random()).normalize(),
+            )
+        })
+        .collect();
+    (world, rays)
+}
+
+#[test]
+fn test_dividing_a_world_keeps_every_object() {
+    let (mut world, _) = random_scene_and_rays();
+    let total = world.objects.len();
+    world.divide(4);
+    assert!(world.objects.len() < total);
+    fn count_leaves(shape: &Shape) -> usize {
+        if shape.children().is_empty() {
+            1
+        } else {
+            shape.children().iter().map(count_leaves).sum()
+        }
+    }
+    assert_eq!(world.objects.iter().map(count_leaves).sum::<usize>(), total);
+}
+
+#[test]
+fn test_a_divided_world_gives_the_same_intersections_as_brute_force() {
+    let (brute_force, rays) = random_scene_and_rays();
+    let (mut bvh, _) = random_scene_and_rays();
+    bvh.divide(4);
+    let mut hits = 0;
+    for ray in rays.iter() {
+        let expected = brute_force.intersect_world(ray);
+        let actual = bvh.intersect_world(ray);
+        assert_eq!(
+            actual.iter().map(|i| i.t).collect::<Vec<_>>(),
+            expected.iter().map(|i| i.t).collect::<Vec<_>>()
+        );
+        let expected_hit = find_hit(&expected);
+        let actual_hit = find_hit(&actual);
+        assert_eq!(
+            actual_hit.map(|hit| hit.object),
+            expected_hit.map(|hit| hit.object)
+        );
+        hits += expected.len();
+    }
+    assert!(hits > 0);
+}